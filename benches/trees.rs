@@ -0,0 +1,133 @@
+// red-black tree vs avl tree vs std BTreeSet
+//
+// every benchmark runs over the same key sequences: sorted, reverse sorted, shuffled with
+// a fixed seed, and zig-zag (smallest, largest, second smallest, ...)
+
+
+use std::collections::BTreeSet;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use datatrees::{AVL_Tree, RedBlackTree};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+const SIZES: [u32; 3] = [100, 1_000, 10_000];
+const SEED: u64 = 0x5eed;
+const ORDERS: [&str; 4] = ["sorted", "reverse", "random", "zig-zag"];
+
+fn keys_in_order(order: &str, size: u32) -> Vec<u32> {
+    let mut keys: Vec<u32> = (0..size).collect();
+    match order {
+        "reverse" => keys.reverse(),
+        "random" => keys.shuffle(&mut StdRng::seed_from_u64(SEED)),
+        "zig-zag" => keys = (0..size / 2).flat_map(|low| [low, size - 1 - low]).chain((size % 2 == 1).then_some(size / 2)).collect(),
+        _ => {}
+    }
+    keys
+}
+
+fn build_rb(keys: &[u32]) -> RedBlackTree<u32> {
+    let mut tree = RedBlackTree::new();
+    for &key in keys {
+        tree.insert(key).unwrap();
+    }
+    tree
+}
+
+fn build_avl(keys: &[u32]) -> AVL_Tree<u32> {
+    let mut tree = AVL_Tree::new();
+    for &key in keys {
+        tree.insert(key).unwrap();
+    }
+    tree
+}
+
+fn build_btree(keys: &[u32]) -> BTreeSet<u32> {
+    keys.iter().copied().collect()
+}
+
+// the finished trees are dropped outside the timed part
+fn bench_insert(c: &mut Criterion) {
+    for order in ORDERS {
+        let mut group = c.benchmark_group(format!("insert/{}", order));
+        for size in SIZES {
+            let keys = keys_in_order(order, size);
+            group.bench_with_input(BenchmarkId::new("rb", size), &keys, |b, keys| b.iter_with_large_drop(|| build_rb(black_box(keys))));
+            group.bench_with_input(BenchmarkId::new("avl", size), &keys, |b, keys| b.iter_with_large_drop(|| build_avl(black_box(keys))));
+            group.bench_with_input(BenchmarkId::new("btreeset", size), &keys, |b, keys| b.iter_with_large_drop(|| build_btree(black_box(keys))));
+        }
+        group.finish();
+    }
+}
+
+// look up every key that was inserted, the tree shape depends on the insertion order
+fn bench_lookup(c: &mut Criterion) {
+    for order in ORDERS {
+        let mut group = c.benchmark_group(format!("lookup/{}", order));
+        for size in SIZES {
+            let keys = keys_in_order(order, size);
+            let mut probes = keys.clone();
+            probes.shuffle(&mut StdRng::seed_from_u64(SEED + 1));
+            let rb = build_rb(&keys);
+            group.bench_with_input(BenchmarkId::new("rb", size), &probes, |b, probes| {
+                b.iter(|| probes.iter().filter(|&key| rb.contains(black_box(key))).count())
+            });
+            let avl = build_avl(&keys);
+            group.bench_with_input(BenchmarkId::new("avl", size), &probes, |b, probes| {
+                b.iter(|| probes.iter().filter(|&key| avl.contains(black_box(key))).count())
+            });
+            let btree = build_btree(&keys);
+            group.bench_with_input(BenchmarkId::new("btreeset", size), &probes, |b, probes| {
+                b.iter(|| probes.iter().filter(|&key| btree.contains(black_box(key))).count())
+            });
+        }
+        group.finish();
+    }
+}
+
+// half of the probes miss, the keys are the even numbers and the probes are drawn from
+// twice the key range
+fn bench_membership(c: &mut Criterion) {
+    for order in ORDERS {
+        let mut group = c.benchmark_group(format!("membership/{}", order));
+        for size in SIZES {
+            let keys: Vec<u32> = keys_in_order(order, size).iter().map(|key| key * 2).collect();
+            let mut rng = StdRng::seed_from_u64(SEED + 2);
+            let probes: Vec<u32> = (0..size).map(|_| rng.gen_range(0..size * 2)).collect();
+            let rb = build_rb(&keys);
+            group.bench_with_input(BenchmarkId::new("rb", size), &probes, |b, probes| {
+                b.iter(|| probes.iter().filter(|&key| rb.contains(black_box(key))).count())
+            });
+            let avl = build_avl(&keys);
+            group.bench_with_input(BenchmarkId::new("avl", size), &probes, |b, probes| {
+                b.iter(|| probes.iter().filter(|&key| avl.contains(black_box(key))).count())
+            });
+            let btree = build_btree(&keys);
+            group.bench_with_input(BenchmarkId::new("btreeset", size), &probes, |b, probes| {
+                b.iter(|| probes.iter().filter(|&key| btree.contains(black_box(key))).count())
+            });
+        }
+        group.finish();
+    }
+}
+
+// in-order walk over the whole tree
+fn bench_iteration(c: &mut Criterion) {
+    for order in ORDERS {
+        let mut group = c.benchmark_group(format!("iteration/{}", order));
+        for size in SIZES {
+            let keys = keys_in_order(order, size);
+            let rb = build_rb(&keys);
+            group.bench_function(BenchmarkId::new("rb", size), |b| b.iter(|| rb.iter().fold(0u64, |sum, key| sum + key as u64)));
+            let avl = build_avl(&keys);
+            group.bench_function(BenchmarkId::new("avl", size), |b| b.iter(|| avl.iter().fold(0u64, |sum, key| sum + key as u64)));
+            let btree = build_btree(&keys);
+            group.bench_function(BenchmarkId::new("btreeset", size), |b| b.iter(|| btree.iter().fold(0u64, |sum, &key| sum + key as u64)));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_insert, bench_lookup, bench_membership, bench_iteration);
+criterion_main!(benches);
@@ -0,0 +1,404 @@
+// non-interactive command line, for scripts and makefiles
+//
+//   datatrees --tree rb insert 5 3 9 --print --height --leaves
+//   datatrees --tree avl --from-file keys.txt --dot out.dot
+//   datatrees --tree avl sequence sorted 1000 --height
+//   datatrees --json --tree rb insert 5 3 9 --height --inorder
+//
+// keys from --from-file go in first, then the generated ones, then the insert and delete
+// lists, and the reports are produced in the order they were given. without any arguments
+// the interactive session starts instead; --script feeds it from a file (see script.rs
+// for the format).
+
+use std::fs;
+use std::io;
+
+use crate::dyntree::{DynTree, KeyType, TreeKind, Walk};
+use crate::json::{parse_json, JsonValue};
+use crate::workload::{parse_workload, Workload};
+use crate::TreeError;
+
+pub const USAGE: &str = "\
+usage: datatrees [--strict] [--json] [--keys TYPE] [--tree rb|avl] [--from-file PATH]
+                 [random ...] [sequence ...] [insert KEY...] [delete KEY...] [REPORT...]
+       datatrees [--strict] [--json] [--keys TYPE] [--script PATH]
+
+options:
+  --tree rb|avl       which tree to build (default rb)
+  --keys TYPE         the key type of the tree, or of every tree in the session:
+                      i32, i64, u64 or i128 (default i64), f64 for floats other
+                      than NaN, string for words in byte order, folded for words
+                      ordered without case; words are shown in double quotes and
+                      may be typed with them, in a session a word with spaces
+                      needs them
+  --from-file PATH    insert the whitespace separated keys in PATH first
+  random COUNT [--seed N] [--range LOW..HIGH]
+                      insert COUNT distinct random keys from LOW up to but not
+                      including HIGH (default 0..10*COUNT), without a seed one is
+                      picked and printed on stderr so the run can be repeated
+  sequence sorted|reverse|zigzag COUNT
+                      insert 0 to COUNT-1 in that order, zigzag alternates between
+                      the smallest and the largest keys left
+  insert KEY...       insert the keys that follow
+  delete KEY...       delete the keys that follow
+  --script PATH       run the session commands in PATH (type help in the session)
+  --strict            stop at the first failing command, duplicate inserts and
+                      deletes of missing keys count as failures
+  --json              answer with one json object per line instead of text, see below
+  -h, --help          show this text
+
+reports, written in the order given:
+  --print             print the tree with its structure
+  --height            print the height
+  --leaves            print the number of leaves
+  --len               print the number of nodes
+  --inorder, --preorder, --postorder, --levels
+                      print the keys in that order
+  --dot PATH          write a graphviz dot file
+  --shape PATH        write a json shape file
+  --svg PATH          write an svg drawing
+
+scripts have one command per line as it would be typed at the prompt, // starts a
+comment line and # a directive: #echo on|off, #strict on|off, #end. the end of the
+script ends the session, no closing quit is needed.
+
+with --json every report, and in the session every command, prints one json object on a
+line of its own and nothing else is printed on stdout. the fields are
+  command             the report or command, e.g. height or inorder
+  tree, ok            session only: the name of the tree in use, and false if the
+                      command failed, with the reasons in errors
+  height, leaves, nodes, empty
+                      the numbers and the answer of empty
+  keys                the keys of a walk or a range, json numbers or strings as in
+                      the shape files
+  levels              the keys of each depth, root first
+  shape               the json shape document of print
+  key, found, depth, path
+                      find: the path holds the nodes passed, each with its key and
+                      its colour (rb) or height (avl), depth is null if not found
+  path                the file a dot, shape or svg report wrote
+  messages            what any other session command would have printed
+explain mode is not available with --json.
+
+exit status: 0 on success, 1 if the tree, a file operation or (with --strict) a command
+failed, 2 for bad arguments
+";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Report {
+    Print,
+    Height,
+    Leaves,
+    Len,
+    InOrder,
+    PreOrder,
+    PostOrder,
+    Levels,
+    Dot(String),
+    Shape(String),
+    Svg(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchOptions {
+    pub tree: TreeKind,
+    pub keys: KeyType,
+    pub from_file: Option<String>,
+    pub generate: Vec<Workload>,
+    pub insert: Vec<String>,
+    pub delete: Vec<String>,
+    pub reports: Vec<Report>,
+    pub strict: bool,
+    pub json: bool, // the reports as json lines
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CliCommand {
+    Help,
+    Batch(BatchOptions),
+    // the session, reading from the script or from stdin
+    Repl { script: Option<String>, strict: bool, keys: KeyType, json: bool },
+}
+
+// which key list the bare words after insert/delete belong to
+enum KeyList {
+    None,
+    Insert,
+    Delete,
+}
+
+// read the arguments (without the program name), errors are usage mistakes
+pub fn parse_args(args: &[String]) -> Result<CliCommand, String> {
+    let mut options = BatchOptions { tree: TreeKind::Rb, keys: KeyType::I64, from_file: None, generate: Vec::new(), insert: Vec::new(), delete: Vec::new(), reports: Vec::new(), strict: false, json: false };
+    let mut script = None;
+    let mut batch = false; // anything but --strict, --json, --keys and --script was given
+    let mut list = KeyList::None;
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().cloned().ok_or(format!("{} needs a value", name));
+        batch |= !matches!(arg.as_str(), "--strict" | "--json" | "--keys" | "--script");
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--strict" => options.strict = true,
+            "--json" => options.json = true,
+            "--script" => script = Some(value("--script")?),
+            "--tree" => {
+                let name = value("--tree")?;
+                options.tree = TreeKind::from_name(&name).ok_or(format!("unknown tree type '{}', expected rb or avl", name))?;
+            }
+            "--keys" => {
+                let name = value("--keys")?;
+                options.keys = KeyType::from_name(&name).ok_or(format!("unknown key type '{}', expected i32, i64, u64, i128, f64, string or folded", name))?;
+            }
+            "--from-file" => options.from_file = Some(value("--from-file")?),
+            "--print" => options.reports.push(Report::Print),
+            "--height" => options.reports.push(Report::Height),
+            "--leaves" => options.reports.push(Report::Leaves),
+            "--len" => options.reports.push(Report::Len),
+            "--inorder" => options.reports.push(Report::InOrder),
+            "--preorder" => options.reports.push(Report::PreOrder),
+            "--postorder" => options.reports.push(Report::PostOrder),
+            "--levels" => options.reports.push(Report::Levels),
+            "--dot" => options.reports.push(Report::Dot(value("--dot")?)),
+            "--shape" => options.reports.push(Report::Shape(value("--shape")?)),
+            "--svg" => options.reports.push(Report::Svg(value("--svg")?)),
+            "random" | "sequence" => {
+                // the words of the command, as the repl would read them
+                let mut words = vec![arg.clone()];
+                let fixed = if arg == "random" { 1 } else { 2 };
+                words.extend(args.by_ref().take(fixed).cloned());
+                while let Some(option) = args.next_if(|next| *next == "--seed" || *next == "--range") {
+                    words.push(option.clone());
+                    words.extend(args.next().cloned());
+                }
+                options.generate.push(parse_workload(&words)?);
+                list = KeyList::None;
+            }
+            "insert" => list = KeyList::Insert,
+            "delete" => list = KeyList::Delete,
+            // negative numbers look like options, whatever their size or type
+            flag if flag.starts_with('-') && flag.parse::<f64>().is_err() => return Err(format!("unknown option '{}'", flag)),
+            key => match list {
+                KeyList::Insert => options.insert.push(key.to_string()),
+                KeyList::Delete => options.delete.push(key.to_string()),
+                KeyList::None => return Err(format!("unexpected '{}', keys go after insert or delete", key)),
+            },
+        }
+    }
+
+    if !batch {
+        return Ok(CliCommand::Repl { script, strict: options.strict, keys: options.keys, json: options.json });
+    }
+    if script.is_some() {
+        return Err("--script cannot be combined with the batch options".to_string());
+    }
+    // keys are checked up front so a typo fails before anything is written
+    for key in options.insert.iter().chain(options.delete.iter()) {
+        options.keys.check(key).map_err(|err| err.to_string())?;
+    }
+    for workload in &options.generate {
+        let (low, high) = workload.bounds();
+        for key in [low, high] {
+            options.keys.check(&key.to_string()).map_err(|err| format!("{}: {}", workload, err))?;
+        }
+    }
+    Ok(CliCommand::Batch(options))
+}
+
+// keep the path in file errors, the bare io error does not say which file it was
+fn with_path(path: &str, err: io::Error) -> TreeError {
+    TreeError::Io(io::Error::new(err.kind(), format!("{}: {}", path, err)))
+}
+
+fn write_file(path: &str, contents: String) -> Result<(), TreeError> {
+    fs::write(path, contents).map_err(|err| with_path(path, err))?;
+    println!("wrote {}", path);
+    Ok(())
+}
+
+// one report as a json object, see USAGE for the fields
+fn json_report(tree: &dyn DynTree, report: &Report) -> Result<JsonValue, TreeError> {
+    let keys = |keys: Vec<JsonValue>| JsonValue::Array(keys);
+    // the path stands in for the wrote line of the text output
+    let write = |path: &String, contents: String| match fs::write(path, contents) {
+        Ok(()) => Ok(JsonValue::String(path.clone())),
+        Err(err) => Err(with_path(path, err)),
+    };
+    let (command, field, value) = match report {
+        Report::Print => ("print", "shape", parse_json(&tree.to_json())?),
+        Report::Height => ("height", "height", JsonValue::number(tree.height())),
+        Report::Leaves => ("leaves", "leaves", JsonValue::number(tree.leaves())),
+        Report::Len => ("len", "nodes", JsonValue::number(tree.len())),
+        Report::InOrder => ("inorder", "keys", keys(tree.keys_json(Walk::In))),
+        Report::PreOrder => ("preorder", "keys", keys(tree.keys_json(Walk::Pre))),
+        Report::PostOrder => ("postorder", "keys", keys(tree.keys_json(Walk::Post))),
+        Report::Levels => ("levels", "levels", JsonValue::Array(tree.levels_json().into_iter().map(keys).collect())),
+        Report::Dot(path) => ("dot", "path", write(path, tree.to_dot())?),
+        Report::Shape(path) => ("shape", "path", write(path, tree.to_json())?),
+        Report::Svg(path) => ("svg", "path", write(path, tree.to_svg())?),
+    };
+    Ok(JsonValue::object(vec![("command", JsonValue::String(command.to_string())), (field, value)]))
+}
+
+fn run_batch_on(tree: &mut dyn DynTree, options: &BatchOptions) -> Result<(), TreeError> {
+    tree.set_strict(options.strict);
+    if let Some(path) = &options.from_file {
+        let text = fs::read_to_string(path).map_err(|err| with_path(path, err))?;
+        for (number, line) in text.lines().enumerate() {
+            for key in line.split_whitespace() {
+                if let Err(err) = options.keys.check(key) {
+                    return Err(TreeError::Parse(format!("{}:{}: {}", path, number + 1, err)));
+                }
+                tree.insert_key(key)?;
+            }
+        }
+    }
+    for workload in &options.generate {
+        let resolved = workload.clone().with_seed();
+        if resolved != *workload {
+            eprintln!("{}", resolved);
+        }
+        for key in resolved.keys() {
+            tree.insert_key(&key.to_string())?;
+        }
+    }
+    for key in &options.insert {
+        tree.insert_key(key)?;
+    }
+    for key in &options.delete {
+        if !tree.delete_key(key)? && options.strict {
+            return Err(TreeError::Command(format!("{} is not in the tree", key)));
+        }
+    }
+
+    for report in &options.reports {
+        if options.json {
+            println!("{}", json_report(tree, report)?);
+            continue;
+        }
+        match report {
+            Report::Print => tree.print(),
+            Report::Height => println!("height: {}", tree.height()),
+            Report::Leaves => println!("leaves: {}", tree.leaves()),
+            Report::Len => println!("nodes: {}", tree.len()),
+            Report::InOrder => println!("in-order: {}", tree.in_order().join(" ")),
+            Report::PreOrder => println!("pre-order: {}", tree.pre_order().join(" ")),
+            Report::PostOrder => println!("post-order: {}", tree.post_order().join(" ")),
+            Report::Levels => tree.print_levels(),
+            Report::Dot(path) => write_file(path, tree.to_dot())?,
+            Report::Shape(path) => write_file(path, tree.to_json())?,
+            Report::Svg(path) => write_file(path, tree.to_svg())?,
+        }
+    }
+    Ok(())
+}
+
+// build the requested tree and produce the reports
+pub fn run_batch(options: &BatchOptions) -> Result<(), TreeError> {
+    return run_batch_on(options.tree.new_tree(options.keys).as_mut(), options);
+}
+
+// everything main does with the arguments, the result is the exit status from USAGE
+pub fn run_args(args: &[String]) -> i32 {
+    let result = match parse_args(args) {
+        Ok(CliCommand::Help) => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        Ok(CliCommand::Repl { script, strict, keys, json }) => crate::run_repl(script.as_deref(), strict, keys, json),
+        Ok(CliCommand::Batch(options)) => run_batch(&options),
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return 2;
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn batch(line: &str) -> BatchOptions {
+        match parse_args(&args(line)) {
+            Ok(CliCommand::Batch(options)) => options,
+            other => panic!("{}: {:?}", line, other),
+        }
+    }
+
+    fn mistake(line: &str) -> String {
+        parse_args(&args(line)).unwrap_err()
+    }
+
+    #[test]
+    fn batch_options_are_read_in_any_order() {
+        let options = batch("--json --tree avl --keys i32 insert 5 -3 9 --height delete 5 --print --from-file keys.txt --dot out.dot --height");
+        assert_eq!(options.tree, TreeKind::Avl);
+        assert_eq!(options.keys, KeyType::I32);
+        assert_eq!(options.from_file.as_deref(), Some("keys.txt"));
+        assert_eq!(options.insert, ["5", "-3", "9"]);
+        assert_eq!(options.delete, ["5"]);
+        assert!(options.json && !options.strict);
+        // reports keep the order they were given in, repeats included
+        assert_eq!(options.reports, [Report::Height, Report::Print, Report::Dot("out.dot".to_string()), Report::Height]);
+    }
+
+    #[test]
+    fn workloads_take_their_own_words() {
+        let options = batch("random 5 --seed 7 --range -10..10 insert 100 sequence zigzag 4 --levels");
+        assert_eq!(options.generate.iter().map(Workload::to_string).collect::<Vec<_>>(), ["random 5 --seed 7 --range -10..10", "sequence zigzag 4"]);
+        assert_eq!(options.insert, ["100"]);
+        assert_eq!(options.reports, [Report::Levels]);
+    }
+
+    #[test]
+    fn session_options_start_the_session() {
+        assert_eq!(parse_args(&args("")), Ok(CliCommand::Repl { script: None, strict: false, keys: KeyType::I64, json: false }));
+        assert_eq!(parse_args(&args("--strict --keys string --script run.txt --json")), Ok(CliCommand::Repl { script: Some("run.txt".to_string()), strict: true, keys: KeyType::String, json: true }));
+        assert_eq!(parse_args(&args("--height -h")), Ok(CliCommand::Help));
+    }
+
+    #[test]
+    fn usage_mistakes_are_explained() {
+        assert_eq!(mistake("--tree splay"), "unknown tree type 'splay', expected rb or avl");
+        assert_eq!(mistake("--keys u8"), "unknown key type 'u8', expected i32, i64, u64, i128, f64, string or folded");
+        assert_eq!(mistake("--dot"), "--dot needs a value");
+        assert_eq!(mistake("--bogus"), "unknown option '--bogus'");
+        assert_eq!(mistake("5 --height"), "unexpected '5', keys go after insert or delete");
+        assert_eq!(mistake("--script run.txt --height"), "--script cannot be combined with the batch options");
+        assert_eq!(mistake("random 0"), "the count must be at least 1");
+        assert!(mistake("--keys u64 insert 1 -1").contains("-1"));
+        assert!(mistake("--keys i32 random 10 --range 0..3000000000").starts_with("random 10 --range 0..3000000000: "));
+    }
+
+    #[test]
+    fn exit_status_tells_usage_mistakes_from_failures() {
+        let dir = std::env::temp_dir().join(format!("datatrees-args-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dot = dir.join("tree.dot").display().to_string();
+        assert_eq!(run_args(&args(&format!("insert 5 3 9 --height --dot {}", dot))), 0);
+        assert!(fs::read_to_string(&dot).unwrap().starts_with("digraph"));
+        assert_eq!(run_args(&args("--help")), 0);
+
+        assert_eq!(run_args(&args("--bogus")), 2);
+        assert_eq!(run_args(&args("--tree")), 2);
+        assert_eq!(run_args(&args("--keys i32 insert 3000000000")), 2);
+
+        // a missing key is only a failure with --strict
+        assert_eq!(run_args(&args("insert 1 delete 2")), 0);
+        assert_eq!(run_args(&args("--strict insert 1 delete 2")), 1);
+        let missing = dir.join("missing.txt").display().to_string();
+        assert_eq!(run_args(&args(&format!("--from-file {} --height", missing))), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
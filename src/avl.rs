@@ -0,0 +1,562 @@
+// AVL tree: the node type, the balancing helpers and the AVL_Tree wrapper
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::observer::{BalanceCase, TreeEvent, TreeObserver, TreeObservers};
+use crate::stats::TreeStats;
+use crate::traversal::{InOrderIter, LevelOrderIter, PostOrderIter, PreOrderIter, RangeIter};
+use crate::TreeError;
+
+#[allow(non_camel_case_types)] // Part of the public api under this name
+pub struct AVL_Tree_Node<T> {
+    pub(crate) value: T,
+    pub(crate) left: Option<Rc<RefCell<AVL_Tree_Node<T>>>>,
+    pub(crate) right: Option<Rc<RefCell<AVL_Tree_Node<T>>>>,
+    pub(crate) height: i32,
+}
+
+// Tear down the children with a stack so dropping a deep tree does not recurse
+impl<T> Drop for AVL_Tree_Node<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Rc<RefCell<AVL_Tree_Node<T>>>> = Vec::new();
+        stack.extend(self.left.take());
+        stack.extend(self.right.take());
+        while let Some(node) = stack.pop() {
+            // only unlink nodes nobody else holds on to
+            if let Ok(cell) = Rc::try_unwrap(node) {
+                let mut node = cell.into_inner();
+                stack.extend(node.left.take());
+                stack.extend(node.right.take());
+            }
+        }
+    }
+}
+
+// AVL Tree Node constructor
+impl<T> AVL_Tree_Node<T> {
+    pub(crate) fn new(value: T) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            value,
+            right: None,
+            left: None,
+            height: 1,
+        }))
+    }
+}
+
+// The entire AVL Tree
+#[allow(non_camel_case_types)] // Part of the public api under this name
+pub struct AVL_Tree<T> {
+    pub(crate) root: Option<Rc<RefCell<AVL_Tree_Node<T>>>>,
+    pub(crate) len: usize, // number of nodes, kept up to date on every insert and delete
+    pub(crate) strict: bool, // report duplicate values as errors instead of ignoring them
+    pub(crate) observers: TreeObservers<T>,
+    pub(crate) last_stats: TreeStats,
+    pub(crate) total_stats: TreeStats,
+}
+
+impl<T> Default for AVL_Tree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// AVL Tree constructor
+impl<T> AVL_Tree<T> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0, strict: false, observers: TreeObservers::new(), last_stats: TreeStats::default(), total_stats: TreeStats::default() }
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // Subscribe to the rotations, height changes and other structural changes
+    pub fn add_observer(&mut self, observer: Box<dyn TreeObserver<T>>) {
+        self.observers.add(observer);
+    }
+
+    // Number of nodes in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Height of the tree, read straight off the root
+    pub fn height(&self) -> i32 {
+        height_avl(&self.root)
+    }
+
+    // Remove every node from the tree
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    // Close the counters of the operation that just finished
+    fn record_stats(&mut self) {
+        self.last_stats = self.observers.take_stats();
+        self.total_stats += self.last_stats;
+    }
+
+    // Counters of the last insert or delete
+    pub fn last_stats(&self) -> TreeStats {
+        self.last_stats
+    }
+
+    // Counters summed over every insert and delete so far
+    pub fn total_stats(&self) -> TreeStats {
+        self.total_stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.last_stats = TreeStats::default();
+        self.total_stats = TreeStats::default();
+    }
+}
+
+pub(crate) fn height_avl<T>(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>) -> i32 {
+    node.as_ref().map_or(0, |node| node.borrow().height)
+}
+
+// The node a height update, rotation or rebalance works on, which is never missing in a sound tree
+fn node_to<'a, T>(node: &'a Option<Rc<RefCell<AVL_Tree_Node<T>>>>, action: &str) -> Result<&'a Rc<RefCell<AVL_Tree_Node<T>>>, TreeError> {
+    node.as_ref().ok_or_else(|| TreeError::InvariantBroken(format!("no node to {}", action)))
+}
+
+pub(crate) fn set_height_avl<T: Clone>(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>, observers: &mut TreeObservers<T>) -> Result<(), TreeError> {
+    let node = node_to(node, "update the height of")?;
+    let left_height = height_avl(&node.borrow().left);
+    let right_height = height_avl(&node.borrow().right);
+    let height = right_height.max(left_height) + 1;
+    observers.stats.height_updates += 1;
+    if node.borrow().height != height {
+        node.borrow_mut().height = height;
+        observers.emit(|| TreeEvent::HeightUpdated { key: node.borrow().value.clone(), height });
+    }
+    Ok(())
+}
+
+pub(crate) fn get_balancing_factor<T>(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>) -> Result<i32, TreeError> {
+    let node = node_to(node, "take the balance of")?;
+    let left_node = &node.borrow().left;
+    let right_node = &node.borrow().right;
+    Ok(height_avl(left_node) - height_avl(right_node))
+}
+
+pub(crate) fn rotate_avl_right<T: Clone>(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>, observers: &mut TreeObservers<T>) -> Result<Rc<RefCell<AVL_Tree_Node<T>>>, TreeError> {
+    let current = node_to(node, "rotate")?;
+    let left = current.borrow().left.clone().ok_or(TreeError::MissingChild("left"))?;
+    let right_node_of_left = left.borrow().right.clone();
+
+    current.borrow_mut().left = right_node_of_left;
+    left.borrow_mut().right = Some(current.clone());
+    observers.emit(|| TreeEvent::RotateRight { node: current.borrow().value.clone(), pivot: left.borrow().value.clone() });
+
+    set_height_avl(node, observers)?;
+    set_height_avl(&Some(left.clone()), observers)?;
+
+    Ok(left)
+}
+
+pub(crate) fn rotate_avl_left<T: Clone>(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>, observers: &mut TreeObservers<T>) -> Result<Rc<RefCell<AVL_Tree_Node<T>>>, TreeError> {
+    let current = node_to(node, "rotate")?;
+    let right = current.borrow().right.clone().ok_or(TreeError::MissingChild("right"))?;
+    let left_node_of_right = right.borrow().left.clone();
+
+    current.borrow_mut().right = left_node_of_right;
+    right.borrow_mut().left = Some(current.clone());
+    observers.emit(|| TreeEvent::RotateLeft { node: current.borrow().value.clone(), pivot: right.borrow().value.clone() });
+
+    set_height_avl(node, observers)?;
+    set_height_avl(&Some(right.clone()), observers)?;
+
+    Ok(right)
+}
+
+pub(crate) fn balance_node_avl<T: Clone>(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>, observers: &mut TreeObservers<T>) -> Result<Rc<RefCell<AVL_Tree_Node<T>>>, TreeError> {
+    set_height_avl(node, observers)?;
+    let current = node_to(node, "rebalance")?;
+
+    let balancing_factor = get_balancing_factor(node)?;
+    if balancing_factor == -2 {
+        let right = current.borrow().right.clone();
+        let right_leans_left = get_balancing_factor(&right)? > 0;
+        let case = if right_leans_left { BalanceCase::RightLeft } else { BalanceCase::RightRight };
+        observers.emit(|| TreeEvent::Rebalance { case, key: current.borrow().value.clone() });
+        if right_leans_left {
+            observers.stats.double_rotations += 1;
+            current.borrow_mut().right = Some(rotate_avl_right(&right, observers)?);
+        } else {
+            observers.stats.single_rotations += 1;
+        }
+        rotate_avl_left(node, observers)
+    } else if balancing_factor == 2 {
+        let left = current.borrow().left.clone();
+        let left_leans_right = get_balancing_factor(&left)? < 0;
+        let case = if left_leans_right { BalanceCase::LeftRight } else { BalanceCase::LeftLeft };
+        observers.emit(|| TreeEvent::Rebalance { case, key: current.borrow().value.clone() });
+        if left_leans_right {
+            observers.stats.double_rotations += 1;
+            current.borrow_mut().left = Some(rotate_avl_left(&left, observers)?);
+        } else {
+            observers.stats.single_rotations += 1;
+        }
+        rotate_avl_right(node, observers)
+    } else if balancing_factor.abs() > 2 {
+        Err(TreeError::InvariantBroken(format!("balancing factor of {}", balancing_factor)))
+    } else {
+        Ok(current.clone())
+    }
+}
+
+
+impl<T: Clone + std::cmp::Ord + std::fmt::Display> AVL_Tree<T> {
+    // Insert a value, returns false if it was already in the tree
+    pub fn insert(&mut self, value: T) -> Result<bool, TreeError> {
+        let inserted = Self::insert_val_avl(&mut self.root, value, &mut self.observers);
+        self.record_stats();
+        let inserted = match inserted {
+            Ok(inserted) => inserted,
+            Err(TreeError::DuplicateKey(_)) if !self.strict => false,
+            Err(err) => return Err(err),
+        };
+        if inserted {
+            self.len += 1;
+        }
+        Ok(inserted)
+    }
+
+    // Values from low to high, both included, in sorted order
+    pub fn range(&self, low: T, high: T) -> RangeIter<AVL_Tree_Node<T>> {
+        RangeIter::new(&self.root, low, high)
+    }
+
+    // Remove a value, returns false if it was not in the tree
+    pub fn remove(&mut self, value: T) -> Result<bool, TreeError> {
+        let removed = Self::delete_val_avl(&mut self.root, value, &mut self.observers);
+        self.record_stats();
+        let removed = removed?;
+        if removed {
+            self.len -= 1;
+        }
+        Ok(removed)
+    }
+
+    // Whether the value is in the tree
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let borrowed_node = node.borrow();
+            if *value < borrowed_node.value {
+                current = borrowed_node.left.clone();
+            } else if *value > borrowed_node.value {
+                current = borrowed_node.right.clone();
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    // The nodes a search for the value passes from the root down, with their heights,
+    // the last one holds the value itself if it is in the tree
+    pub fn search_path(&self, value: &T) -> Vec<(T, i32)> {
+        let mut path = Vec::new();
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let borrowed_node = node.borrow();
+            path.push((borrowed_node.value.clone(), borrowed_node.height));
+            current = if *value < borrowed_node.value {
+                borrowed_node.left.clone()
+            } else if *value > borrowed_node.value {
+                borrowed_node.right.clone()
+            } else {
+                None
+            };
+        }
+        path
+    }
+
+    // Insert a node to the AVL tree, fails with DuplicateKey if the value was already there
+    fn insert_val_avl(root: &mut Option<Rc<RefCell<AVL_Tree_Node<T>>>>, value: T, observers: &mut TreeObservers<T>) -> Result<bool, TreeError> {
+        // walk down to the insertion point, remembering the path so it can be rebalanced bottom-up
+        let mut path: Vec<Rc<RefCell<AVL_Tree_Node<T>>>> = Vec::new();
+        let mut goes_left = false;
+        let mut current = root.clone();
+        while let Some(node) = current {
+            observers.stats.comparisons += 1;
+            {
+                let borrowed_node = node.borrow();
+                if value < borrowed_node.value {
+                    current = borrowed_node.left.clone();
+                    goes_left = true;
+                } else if value > borrowed_node.value {
+                    current = borrowed_node.right.clone();
+                    goes_left = false;
+                } else {
+                    return Err(TreeError::DuplicateKey(value.to_string())); // the value is already in the tree
+                }
+            }
+            path.push(node);
+        }
+
+        observers.emit(|| TreeEvent::NodeCreated { key: value.clone() });
+        if let Some(parent) = path.last() {
+            if goes_left {
+                parent.borrow_mut().left = Some(AVL_Tree_Node::new(value));
+            } else {
+                parent.borrow_mut().right = Some(AVL_Tree_Node::new(value));
+            }
+        } else {
+            *root = Some(AVL_Tree_Node::new(value));
+            observers.step(|| Self::write_avl_tree_picture(root));
+            return Ok(true);
+        }
+        observers.step(|| Self::write_avl_tree_picture(root));
+
+        Self::rebalance_path_avl(root, path, observers)?;
+        Ok(true)
+    }
+
+    // Delete a node from the AVL tree, returns false if the value was not there
+    fn delete_val_avl(root: &mut Option<Rc<RefCell<AVL_Tree_Node<T>>>>, value: T, observers: &mut TreeObservers<T>) -> Result<bool, TreeError> {
+        // walk down to the node, remembering the path so it can be rebalanced bottom-up
+        let mut path: Vec<Rc<RefCell<AVL_Tree_Node<T>>>> = Vec::new();
+        let mut current = root.clone();
+        let target = loop {
+            let node = match current {
+                Some(node) => node,
+                None => return Ok(false), // the value is not in the tree
+            };
+            observers.stats.comparisons += 1;
+            let ordering = value.cmp(&node.borrow().value);
+            current = match ordering {
+                std::cmp::Ordering::Less => node.borrow().left.clone(),
+                std::cmp::Ordering::Greater => node.borrow().right.clone(),
+                std::cmp::Ordering::Equal => break node,
+            };
+            path.push(node);
+        };
+
+        observers.emit(|| TreeEvent::NodeRemoved { key: value.clone() });
+        // A node with two children takes over the value of its successor, which is removed instead
+        let has_two_children = target.borrow().left.is_some() && target.borrow().right.is_some();
+        let removed = if has_two_children {
+            path.push(target.clone());
+            let mut successor = target.borrow().right.clone().ok_or(TreeError::MissingChild("right"))?;
+            loop {
+                let left = successor.borrow().left.clone();
+                match left {
+                    Some(left) => {
+                        path.push(successor);
+                        successor = left;
+                    }
+                    None => break,
+                }
+            }
+            let successor_value = successor.borrow().value.clone();
+            target.borrow_mut().value = successor_value;
+            successor
+        } else {
+            target
+        };
+
+        // The removed node has at most one child, which takes its place
+        let child = {
+            let borrowed_node = removed.borrow();
+            borrowed_node.left.clone().or(borrowed_node.right.clone())
+        };
+        if let Some(parent) = path.last() {
+            let mut borrowed_parent = parent.borrow_mut();
+            if borrowed_parent.left.as_ref().is_some_and(|left| Rc::ptr_eq(left, &removed)) {
+                borrowed_parent.left = child;
+            } else {
+                borrowed_parent.right = child;
+            }
+        } else {
+            *root = child;
+        }
+        observers.step(|| Self::write_avl_tree_picture(root));
+
+        Self::rebalance_path_avl(root, path, observers)?;
+        Ok(true)
+    }
+
+    // Rebalance every node on the path, deepest first, and hang the result back under its parent
+    fn rebalance_path_avl(root: &mut Option<Rc<RefCell<AVL_Tree_Node<T>>>>, mut path: Vec<Rc<RefCell<AVL_Tree_Node<T>>>>, observers: &mut TreeObservers<T>) -> Result<(), TreeError> {
+        while let Some(node) = path.pop() {
+            let balanced = balance_node_avl(&Some(node.clone()), observers)?;
+            let rotated = !Rc::ptr_eq(&balanced, &node);
+            if let Some(parent) = path.last() {
+                let mut borrowed_parent = parent.borrow_mut();
+                if borrowed_parent.left.as_ref().is_some_and(|left| Rc::ptr_eq(left, &node)) {
+                    borrowed_parent.left = Some(balanced);
+                } else {
+                    borrowed_parent.right = Some(balanced);
+                }
+            } else {
+                *root = Some(balanced);
+            }
+            if rotated {
+                observers.step(|| Self::write_avl_tree_picture(root));
+            }
+        }
+        Ok(())
+    }
+
+
+    // Check the ordering, the stored heights, the balancing factors and the size
+    pub fn validate(&self) -> Result<(), TreeError> {
+        let mut count = 0;
+        // nodes still to check, with the nodes whose values bound theirs from below and above
+        let mut stack = Vec::new();
+        if let Some(root) = &self.root {
+            stack.push((root.clone(), None::<Rc<RefCell<AVL_Tree_Node<T>>>>, None::<Rc<RefCell<AVL_Tree_Node<T>>>>));
+        }
+        while let Some((node, lower, upper)) = stack.pop() {
+            count += 1;
+            let borrowed_node = node.borrow();
+            if lower.as_ref().is_some_and(|lower| borrowed_node.value <= lower.borrow().value)
+            || upper.as_ref().is_some_and(|upper| borrowed_node.value >= upper.borrow().value) {
+                return Err(TreeError::InvariantBroken(format!("{} is out of order", borrowed_node.value)));
+            }
+            // the heights of the children are checked when they are popped, so checking each node against them is enough
+            let left_height = height_avl(&borrowed_node.left);
+            let right_height = height_avl(&borrowed_node.right);
+            if borrowed_node.height != left_height.max(right_height) + 1 {
+                return Err(TreeError::InvariantBroken(format!("{} has a stale height of {}", borrowed_node.value, borrowed_node.height)));
+            }
+            if (left_height - right_height).abs() > 1 {
+                return Err(TreeError::InvariantBroken(format!("{} has a balancing factor of {}", borrowed_node.value, left_height - right_height)));
+            }
+            stack.extend(borrowed_node.left.clone().map(|left| (left, lower.clone(), Some(node.clone()))));
+            stack.extend(borrowed_node.right.clone().map(|right| (right, Some(node.clone()), upper.clone())));
+        }
+        if count != self.len {
+            return Err(TreeError::InvariantBroken(format!("{} nodes but a size of {}", count, self.len)));
+        }
+        Ok(())
+    }
+
+    // Count the number of leaves in the tree from a certain root node
+    pub(crate) fn count_leaves_avl(root: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>) -> usize {
+        let mut leaves = 0;
+        // nodes still to visit, starting from this root if it is not empty
+        let mut stack: Vec<Rc<RefCell<AVL_Tree_Node<T>>>> = root.iter().cloned().collect();
+        while let Some(node) = stack.pop() {
+            let borrowed_node = node.borrow();
+            match (&borrowed_node.left, &borrowed_node.right) {
+                (None, None) => leaves += 1, // This is a leaf node
+                // Else, visit the right and left nodes later
+                _ => {
+                    stack.extend(borrowed_node.left.clone());
+                    stack.extend(borrowed_node.right.clone());
+                }
+            }
+        }
+        leaves
+    }
+
+    // Number of leaves in the whole tree
+    pub fn count_leaves(&self) -> usize {
+        Self::count_leaves_avl(&self.root)
+    }
+}
+
+impl<T: Clone> AVL_Tree<T> {
+    // Values in sorted order
+    pub fn iter(&self) -> InOrderIter<AVL_Tree_Node<T>> {
+        InOrderIter::new(&self.root)
+    }
+
+    pub fn pre_order(&self) -> PreOrderIter<AVL_Tree_Node<T>> {
+        PreOrderIter::new(&self.root)
+    }
+
+    pub fn post_order(&self) -> PostOrderIter<AVL_Tree_Node<T>> {
+        PostOrderIter::new(&self.root)
+    }
+
+    // Values level by level, paired with their depth
+    pub fn level_order(&self) -> LevelOrderIter<AVL_Tree_Node<T>> {
+        LevelOrderIter::new(&self.root)
+    }
+}
+
+impl <T: std::fmt::Display> AVL_Tree<T> {
+
+    pub(crate) fn print_avl_tree(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>, prefix: &str, is_left: bool) {
+        let mut out = String::new();
+        Self::write_avl_tree(node, prefix, is_left, &mut out);
+        print!("{}", out);
+    }
+
+    // The whole tree drawn as print_avl_tree draws it, collected into a string
+    pub(crate) fn write_avl_tree_picture(root: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>) -> String {
+        let mut out = String::new();
+        Self::write_avl_tree(root, "", false, &mut out);
+        out
+    }
+
+    fn write_avl_tree(node: &Option<Rc<RefCell<AVL_Tree_Node<T>>>>, prefix: &str, is_left: bool, out: &mut String) {
+        // From: https://www.georgevreilly.com/blog/2023/01/24/TreeInRust2PrintingTrees.html#:~:text=Implementing%20the%20Tree%20command%20in%20Rust%2C%20part%202%3A,printing%20the%20directory%20tree%20with%20Box%20Drawing%20characters. 
+        // Nodes still to draw with the prefix of their line, the left child comes off first
+        let mut stack: Vec<_> = node.iter().map(|n| (n.clone(), prefix.to_string(), is_left)).collect();
+        while let Some((n, prefix, is_left)) = stack.pop() {
+            let borrowed_node = n.borrow();
+            out.push_str(&format!("{}{}{}\n", prefix, if is_left { "├──" } else { "└──" }, borrowed_node.value));
+            let prefix = format!("{}{}", prefix, if is_left { "│   " } else { "    " });
+            stack.extend(borrowed_node.right.clone().map(|right| (right, prefix.clone(), false)));
+            stack.extend(borrowed_node.left.clone().map(|left| (left, prefix, true)));
+        }
+    }
+
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_missing_node_is_a_broken_invariant() {
+        let mut observers = TreeObservers::<u32>::new();
+        let err = rotate_avl_left(&None, &mut observers).err().unwrap();
+        assert!(matches!(err, TreeError::InvariantBroken(_)));
+        assert_eq!(err.to_string(), "tree invariant broken: no node to rotate");
+        assert_eq!(get_balancing_factor::<u32>(&None).unwrap_err().to_string(), "tree invariant broken: no node to take the balance of");
+    }
+
+    // Deleting takes every rebalancing path on the way up, so check the heights after each step
+    #[test]
+    fn random_edits_match_btreeset() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::BTreeSet;
+
+        let mut rng = StdRng::seed_from_u64(41);
+        let mut tree = AVL_Tree::new();
+        let mut expected = BTreeSet::new();
+        for _ in 0..20_000 {
+            let key: u32 = rng.gen_range(0..500);
+            if rng.gen_bool(0.5) {
+                assert_eq!(tree.insert(key).unwrap(), expected.insert(key));
+            } else {
+                assert_eq!(tree.remove(key).unwrap(), expected.remove(&key));
+            }
+            tree.validate().unwrap();
+            assert_eq!(tree.len(), expected.len());
+        }
+        assert!(tree.iter().eq(expected.iter().copied()));
+        for key in expected {
+            assert!(tree.remove(key).unwrap());
+            tree.validate().unwrap();
+        }
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,1012 @@
+// the interactive session: one command per line (see command.rs for the grammar), run
+// against any number of named red-black and AVL trees that live until the session ends
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::command::{parse_command, Command, HELP};
+use crate::dyntree::{DynTree, KeyType, TreeKind, Walk};
+use crate::json::{parse_json, JsonValue};
+use crate::savefile::{read_session, write_session};
+use crate::script::ReplInput;
+use crate::workload::Workload;
+use crate::TreeError;
+
+// how many insert and delete commands each tree remembers for undo
+const HISTORY_LIMIT: usize = 100;
+
+// one change made by an insert or delete command, undo applies the opposite
+enum Edit {
+    Inserted(String),
+    Deleted(String),
+}
+
+fn keys_of(edits: &[Edit]) -> Vec<&str> {
+    edits.iter().map(|edit| match edit {
+        Edit::Inserted(key) | Edit::Deleted(key) => key.as_str(),
+    }).collect()
+}
+
+// the command that made the edits, e.g. "insert 4 8 15", long ones are cut short
+fn describe(edits: &[Edit]) -> String {
+    const SHOWN: usize = 8;
+    let verb = match edits.first() {
+        Some(Edit::Deleted(_)) => "delete",
+        _ => "insert",
+    };
+    let keys = keys_of(edits);
+    if keys.len() > SHOWN {
+        return format!("{} {} ... ({} keys)", verb, keys[..SHOWN].join(" "), keys.len());
+    }
+    format!("{} {}", verb, keys.join(" "))
+}
+
+fn type_name(kind: TreeKind) -> &'static str {
+    match kind {
+        TreeKind::Rb => "red-black",
+        TreeKind::Avl => "AVL",
+    }
+}
+
+// two drawings next to each other, each under its title
+fn side_by_side(left_title: &str, left: &str, right_title: &str, right: &str) -> String {
+    let left_lines: Vec<&str> = std::iter::once(left_title).chain(left.lines()).collect();
+    let right_lines: Vec<&str> = std::iter::once(right_title).chain(right.lines()).collect();
+    let width = left_lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) + 4;
+    let mut out = String::new();
+    for row in 0..left_lines.len().max(right_lines.len()) {
+        let left_line = left_lines.get(row).copied().unwrap_or("");
+        let right_line = right_lines.get(row).copied().unwrap_or("");
+        let padding = width - left_line.chars().count();
+        out.push_str(format!("{}{:padding$}{}", left_line, "", right_line).trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// a tree of the session with its own undo history
+struct NamedTree {
+    tree: Box<dyn DynTree>,
+    history: VecDeque<Vec<Edit>>, // the edits of each insert or delete command, oldest first
+    undone: Vec<Vec<Edit>>, // commands taken back by undo, the most recent last
+    mirror: Option<Box<dyn DynTree>>, // the same keys in a tree of the other type, in dual mode
+}
+
+impl NamedTree {
+    fn new(tree: Box<dyn DynTree>) -> Self {
+        Self { tree, history: VecDeque::new(), undone: Vec::new(), mirror: None }
+    }
+
+    // remember a new command, which also makes whatever was undone unreachable
+    fn record(&mut self, edits: Vec<Edit>) {
+        self.history.push_back(edits);
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.undone.clear();
+    }
+
+    fn forget_history(&mut self) {
+        self.history.clear();
+        self.undone.clear();
+    }
+
+    // make the edits again, or take them back newest first
+    fn replay(&mut self, edits: &[Edit], forward: bool) -> Result<(), TreeError> {
+        if forward {
+            for edit in edits {
+                match edit {
+                    Edit::Inserted(key) => self.insert_key(key)?,
+                    Edit::Deleted(key) => self.delete_key(key)?,
+                };
+            }
+        } else {
+            for edit in edits.iter().rev() {
+                match edit {
+                    Edit::Inserted(key) => self.delete_key(key)?,
+                    Edit::Deleted(key) => self.insert_key(key)?,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    // insert into the tree and its mirror, which hold the same type of key
+    fn insert_key(&mut self, key: &str) -> Result<bool, TreeError> {
+        let inserted = self.tree.insert_key(key)?;
+        if let Some(mirror) = &mut self.mirror {
+            mirror.insert_key(key)?;
+        }
+        Ok(inserted)
+    }
+
+    fn delete_key(&mut self, key: &str) -> Result<bool, TreeError> {
+        let deleted = self.tree.delete_key(key)?;
+        if let Some(mirror) = &mut self.mirror {
+            mirror.delete_key(key)?;
+        }
+        Ok(deleted)
+    }
+
+    // build the mirror again after the tree was replaced
+    fn refresh_mirror(&mut self) -> Result<(), TreeError> {
+        if self.mirror.is_some() {
+            self.mirror = Some(self.tree.copy_as(self.tree.kind().other())?);
+        }
+        Ok(())
+    }
+
+    // rotations done so far by the tree and its mirror
+    fn rotations(&self) -> (u64, u64) {
+        let mirror = self.mirror.as_ref().map_or(0, |mirror| mirror.total_stats().rotations());
+        (self.tree.total_stats().rotations(), mirror)
+    }
+}
+
+struct Session {
+    name: String, // of the tree in use
+    current: NamedTree,
+    others: BTreeMap<String, NamedTree>,
+    keys: KeyType, // of every tree in the session
+    explain: Rc<Cell<bool>>, // shared with the explainer of every tree the session makes
+    pauses: bool, // explain waits for enter, only when someone is typing
+    said: Option<RefCell<Vec<String>>>, // with --json, what the command said for its object
+    answers: Option<Vec<JsonValue>>, // the json objects, kept instead of printed once asked for
+}
+
+impl Session {
+    fn new(kind: TreeKind, keys: KeyType, pauses: bool, json: bool) -> Self {
+        let explain = Rc::new(Cell::new(false));
+        let mut tree = kind.new_tree(keys);
+        tree.add_explainer(explain.clone(), pauses);
+        let current = NamedTree::new(tree);
+        let said = json.then(|| RefCell::new(Vec::new()));
+        Self { name: "main".to_string(), current, others: BTreeMap::new(), keys, explain, pauses, said, answers: None }
+    }
+
+    // a line of output, printed right away or kept for the json object of the command
+    fn say(&self, line: impl Into<String>) {
+        match &self.said {
+            Some(said) => said.borrow_mut().push(line.into()),
+            None => println!("{}", line.into()),
+        }
+    }
+
+    // an empty tree that explains itself when the session asks for it
+    fn new_tree(&self, kind: TreeKind) -> Box<dyn DynTree> {
+        let mut tree = kind.new_tree(self.keys);
+        tree.add_explainer(self.explain.clone(), self.pauses);
+        tree
+    }
+
+    fn get(&self, name: &str) -> Option<&NamedTree> {
+        if name == self.name {
+            return Some(&self.current);
+        }
+        self.others.get(name)
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    // a new tree of the given type holding the keys of tree, the explainer comes in after
+    // the keys so carrying them over is not narrated
+    fn copy_of(&self, tree: &dyn DynTree, kind: TreeKind) -> Result<Box<dyn DynTree>, TreeError> {
+        let mut copy = tree.copy_as(kind)?;
+        copy.add_explainer(self.explain.clone(), self.pauses);
+        Ok(copy)
+    }
+
+    // typed keys the way the trees print them
+    fn show_all(&self, keys: Vec<&str>) -> String {
+        keys.iter().map(|key| self.keys.show(key)).collect::<Vec<String>>().join(" ")
+    }
+
+    fn insert(&mut self, keys: Vec<String>, input: &mut ReplInput) -> Result<(), TreeError> {
+        let mut edits = Vec::new();
+        for key in keys {
+            match self.current.insert_key(&key) {
+                Ok(true) => edits.push(Edit::Inserted(key)),
+                Ok(false) => input.complain(&format!("{} is already in the tree", self.keys.show(&key)))?,
+                Err(err) => input.complain(&err.to_string())?,
+            }
+        }
+        if !edits.is_empty() {
+            self.say(format!("inserted {}", self.show_all(keys_of(&edits))));
+            self.current.record(edits);
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, keys: Vec<String>, input: &mut ReplInput) -> Result<(), TreeError> {
+        let mut edits = Vec::new();
+        for key in keys {
+            match self.current.delete_key(&key) {
+                Ok(true) => edits.push(Edit::Deleted(key)),
+                Ok(false) => input.complain(&format!("{} is not in the tree", self.keys.show(&key)))?,
+                Err(err) => input.complain(&err.to_string())?,
+            }
+        }
+        if !edits.is_empty() {
+            self.say(format!("deleted {}", self.show_all(keys_of(&edits))));
+            self.current.record(edits);
+        }
+        Ok(())
+    }
+
+    // insert generated keys as one command, with a summary instead of every key
+    fn generate(&mut self, workload: Workload, input: &mut ReplInput) -> Result<(), TreeError> {
+        // the whole range is checked, not just the keys this seed happens to pick
+        let (low, high) = workload.bounds();
+        for key in [low, high] {
+            if let Err(err) = self.keys.check(&key.to_string()) {
+                return input.complain(&format!("cannot run {}: {}", workload, err));
+            }
+        }
+        let workload = workload.with_seed();
+        let mut edits = Vec::new();
+        let mut present = 0;
+        for key in workload.keys() {
+            let key = key.to_string();
+            if self.current.insert_key(&key)? {
+                edits.push(Edit::Inserted(key));
+            } else {
+                present += 1;
+            }
+        }
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        self.say(format!("inserted {} key{} from {}", edits.len(), plural(edits.len()), workload));
+        if present > 0 {
+            self.say(format!("{} of them {} already in the tree", present, if present == 1 { "was" } else { "were" }));
+        }
+        if !edits.is_empty() {
+            self.current.record(edits);
+        }
+        Ok(())
+    }
+
+    // take back the last count insert or delete commands, newest first
+    fn undo(&mut self, count: usize, input: &mut ReplInput) -> Result<(), TreeError> {
+        for _ in 0..count {
+            let edits = match self.current.history.pop_back() {
+                Some(edits) => edits,
+                None => return input.complain("nothing more to undo"),
+            };
+            self.current.replay(&edits, false)?;
+            self.say(format!("undid {}", describe(&edits)));
+            self.current.undone.push(edits);
+        }
+        Ok(())
+    }
+
+    // make the last count undone commands again, oldest first
+    fn redo(&mut self, count: usize, input: &mut ReplInput) -> Result<(), TreeError> {
+        for _ in 0..count {
+            let edits = match self.current.undone.pop() {
+                Some(edits) => edits,
+                None => return input.complain("nothing more to redo"),
+            };
+            self.current.replay(&edits, true)?;
+            self.say(format!("redid {}", describe(&edits)));
+            self.current.history.push_back(edits);
+        }
+        Ok(())
+    }
+
+    fn print_history(&self) {
+        if self.current.history.is_empty() && self.current.undone.is_empty() {
+            self.say(format!("no history for {}", self.name));
+            return;
+        }
+        self.say(format!("history of {}, oldest first:", self.name));
+        for (number, edits) in self.current.history.iter().enumerate() {
+            self.say(format!("{:>4}  {}", number + 1, describe(edits)));
+        }
+        // what redo would bring back, next one first
+        for (number, edits) in self.current.undone.iter().rev().enumerate() {
+            self.say(format!("{:>4}  {}  (undone)", self.current.history.len() + number + 1, describe(edits)));
+        }
+    }
+
+    // carry the keys over into an empty tree of the other type
+    fn switch(&mut self, kind: TreeKind, input: &mut ReplInput) -> Result<(), TreeError> {
+        if kind == self.current.tree.kind() {
+            self.say(format!("{} already is an {} tree", self.name, kind.name()));
+            return Ok(());
+        }
+        match self.copy_of(self.current.tree.as_ref(), kind) {
+            Ok(tree) => self.current.tree = tree,
+            Err(err) => return input.complain(&format!("cannot switch to {}: {}", kind.name(), err)),
+        }
+        self.current.refresh_mirror()?;
+        self.say(format!("switched {} to {}, {} keys carried over", self.name, kind.name(), self.current.tree.len()));
+        Ok(())
+    }
+
+    fn new_named(&mut self, kind: TreeKind, name: String, input: &mut ReplInput) -> Result<(), TreeError> {
+        if self.exists(&name) {
+            return input.complain(&format!("there is already a tree called {}", name));
+        }
+        let tree = NamedTree::new(self.new_tree(kind));
+        let previous = std::mem::replace(&mut self.current, tree);
+        self.others.insert(std::mem::replace(&mut self.name, name), previous);
+        self.say(format!("using the new {} tree {}", type_name(kind), self.name));
+        Ok(())
+    }
+
+    fn use_named(&mut self, name: String, input: &mut ReplInput) -> Result<(), TreeError> {
+        if name == self.name {
+            self.say(format!("already using {}", name));
+            return Ok(());
+        }
+        let tree = match self.others.remove(&name) {
+            Some(tree) => tree,
+            None => return input.complain(&format!("there is no tree called {}", name)),
+        };
+        let previous = std::mem::replace(&mut self.current, tree);
+        self.others.insert(std::mem::replace(&mut self.name, name), previous);
+        self.say(format!("using the {} tree {} with {} keys", type_name(self.current.tree.kind()), self.name, self.current.tree.len()));
+        Ok(())
+    }
+
+    // the tree in use and the others, in alphabetical order
+    fn names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.others.keys().collect();
+        names.push(&self.name);
+        names.sort();
+        names
+    }
+
+    fn list(&self) {
+        for name in self.names() {
+            if let Some(named) = self.get(name) {
+                let marker = if *name == self.name { "*" } else { " " };
+                self.say(format!("{} {} ({}, {} keys, height {})", marker, name, named.tree.kind().name(), named.tree.len(), named.tree.height()));
+            }
+        }
+    }
+
+    fn drop_named(&mut self, name: String, input: &mut ReplInput) -> Result<(), TreeError> {
+        if name == self.name {
+            return input.complain(&format!("{} is in use, use another tree before dropping it", name));
+        }
+        match self.others.remove(&name) {
+            Some(_) => self.say(format!("dropped {}", name)),
+            None => input.complain(&format!("there is no tree called {}", name))?,
+        }
+        Ok(())
+    }
+
+    fn copy(&mut self, from: String, to: String, kind: Option<TreeKind>, input: &mut ReplInput) -> Result<(), TreeError> {
+        if self.exists(&to) {
+            return input.complain(&format!("there is already a tree called {}", to));
+        }
+        let source = match self.get(&from) {
+            Some(named) => named.tree.as_ref(),
+            None => return input.complain(&format!("there is no tree called {}", from)),
+        };
+        let kind = kind.unwrap_or(source.kind());
+        let copy = match self.copy_of(source, kind) {
+            Ok(copy) => copy,
+            Err(err) => return input.complain(&format!("cannot copy {} into a {} tree: {}", from, kind.name(), err)),
+        };
+        self.say(format!("copied {} keys from {} into the {} tree {}", copy.len(), from, type_name(kind), to));
+        self.others.insert(to, NamedTree::new(copy));
+        Ok(())
+    }
+
+    // the keys only one of the trees holds, then both shapes
+    fn compare(&self, first: &str, second: &str, input: &mut ReplInput) -> Result<(), TreeError> {
+        let (a, b) = match (self.get(first), self.get(second)) {
+            (Some(a), Some(b)) => (a.tree.as_ref(), b.tree.as_ref()),
+            (None, _) => return input.complain(&format!("there is no tree called {}", first)),
+            (_, None) => return input.complain(&format!("there is no tree called {}", second)),
+        };
+        let only_a = a.missing_from(b);
+        let only_b = b.missing_from(a);
+        let shared = a.len() - only_a.len();
+        if only_a.is_empty() && only_b.is_empty() {
+            self.say(format!("{} and {} hold the same {} keys", first, second, shared));
+        } else {
+            self.say(format!("{} keys in both", shared));
+            self.say(format!("only in {}: {}", first, if only_a.is_empty() { "-".to_string() } else { only_a.join(" ") }));
+            self.say(format!("only in {}: {}", second, if only_b.is_empty() { "-".to_string() } else { only_b.join(" ") }));
+        }
+        for (name, tree) in [(first, a), (second, b)] {
+            self.say(format!("{} ({}): height {}, {} leaves", name, tree.kind().name(), tree.height(), tree.leaves()));
+        }
+        Ok(())
+    }
+
+    fn write_file(&self, path: &str, contents: String, input: &mut ReplInput) -> Result<(), TreeError> {
+        match fs::write(path, contents) {
+            Ok(()) => self.say(format!("wrote {}", path)),
+            Err(err) => input.complain(&format!("could not write {}: {}", path, err))?,
+        }
+        Ok(())
+    }
+
+    fn import(&mut self, path: &str, input: &mut ReplInput) -> Result<(), TreeError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => return input.complain(&format!("could not read {}: {}", path, err)),
+        };
+        match self.current.tree.load_json(&text) {
+            Ok(()) => {
+                // the old edits make no sense on the new keys
+                self.current.forget_history();
+                self.current.refresh_mirror()?;
+                self.say(format!("loaded {} nodes from {}", self.current.tree.len(), path));
+            }
+            Err(err) => input.complain(&err.to_string())?,
+        }
+        Ok(())
+    }
+
+    fn save(&self, path: &str, input: &mut ReplInput) -> Result<(), TreeError> {
+        let trees: Vec<(&str, &dyn DynTree, bool)> = self.names().into_iter()
+            .filter_map(|name| self.get(name).map(|named| (name.as_str(), named.tree.as_ref(), named.mirror.is_some())))
+            .collect();
+        match fs::write(path, write_session(&self.name, self.keys, &trees)) {
+            Ok(()) => self.say(format!("saved {} trees to {}", trees.len(), path)),
+            Err(err) => input.complain(&format!("could not write {}: {}", path, err))?,
+        }
+        Ok(())
+    }
+
+    // replace every tree of the session with the ones in the file
+    fn load(&mut self, path: &str, input: &mut ReplInput) -> Result<(), TreeError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => return input.complain(&format!("could not read {}: {}", path, err)),
+        };
+        let (current_name, keys, saved) = match read_session(&text) {
+            Ok(session) => session,
+            Err(err) => return input.complain(&format!("could not load {}: {}", path, err)),
+        };
+        let count = saved.len();
+        let mut current = None;
+        let mut others = BTreeMap::new();
+        for saved in saved {
+            let mut tree = saved.tree;
+            tree.add_explainer(self.explain.clone(), self.pauses);
+            let mut named = NamedTree::new(tree);
+            if saved.dual {
+                named.mirror = Some(named.tree.kind().other().new_tree(keys));
+                named.refresh_mirror()?;
+            }
+            if saved.name == current_name {
+                current = Some(named);
+            } else {
+                others.insert(saved.name, named);
+            }
+        }
+        self.current = match current {
+            Some(current) => current,
+            None => return input.complain(&format!("could not load {}: the current tree is missing", path)),
+        };
+        self.name = current_name;
+        self.others = others;
+        self.keys = keys;
+        self.say(format!("loaded {} trees with {} keys from {}, using {}", count, keys.name(), path, self.name));
+        Ok(())
+    }
+
+    fn dual(&mut self, on: Option<bool>, input: &mut ReplInput) -> Result<(), TreeError> {
+        if !on.unwrap_or(self.current.mirror.is_none()) {
+            self.current.mirror = None;
+            self.say(format!("dual mode is off for {}", self.name));
+            return Ok(());
+        }
+        let kind = self.current.tree.kind().other();
+        if self.current.mirror.is_none() {
+            match self.current.tree.copy_as(kind) {
+                Ok(mirror) => self.current.mirror = Some(mirror),
+                Err(err) => return input.complain(&format!("cannot mirror {} into {}: {}", self.name, kind.name(), err)),
+            }
+        }
+        self.say(format!("dual mode is on for {}, inserts and deletes also go to an {} mirror", self.name, kind.name()));
+        self.print_dual(self.current.rotations());
+        Ok(())
+    }
+
+    // the comparison table and both drawings, rotations are counted from before
+    fn print_dual(&self, before: (u64, u64)) {
+        let tree = &self.current.tree;
+        let mirror = match &self.current.mirror {
+            Some(mirror) => mirror,
+            None => return,
+        };
+        // a mirror built again during the command counts from zero
+        let (rotations, mirror_rotations) = self.current.rotations();
+        self.say(format!("{:<20}{:>8}{:>8}", "", tree.kind().name(), mirror.kind().name()));
+        self.say(format!("{:<20}{:>8}{:>8}", "height", tree.height(), mirror.height()));
+        self.say(format!("{:<20}{:>8}{:>8}", "leaves", tree.leaves(), mirror.leaves()));
+        self.say(format!("{:<20}{:>8}{:>8}", "rotations", rotations.saturating_sub(before.0), mirror_rotations.saturating_sub(before.1)));
+        self.say(format!("{:<20}{:>8}{:>8}", "rotations in total", rotations, mirror_rotations));
+        self.say("");
+        let title = format!("{} ({})", self.name, tree.kind().name());
+        let mirror_title = format!("mirror ({})", mirror.kind().name());
+        for line in side_by_side(&title, &tree.picture(), &mirror_title, &mirror.picture()).lines() {
+            self.say(line);
+        }
+    }
+
+    // run one command, false once the session is over
+    fn execute(&mut self, command: Command, input: &mut ReplInput) -> Result<bool, TreeError> {
+        if self.said.is_none() {
+            // with explain on the output of the command is set apart from the steps
+            let framed = self.explain.get();
+            if framed {
+                println!("---");
+            }
+            let going = self.perform(command, input)?;
+            if going && framed {
+                println!("---");
+            }
+            return Ok(going);
+        }
+        let name = command.name();
+        let answered = match self.query(&command, input) {
+            Ok(Some(fields)) => Ok((fields, true)),
+            Ok(None) => self.perform(command, input).map(|going| (Vec::new(), going)),
+            Err(err) => Err(err),
+        };
+        match answered {
+            Ok((fields, going)) => {
+                self.answer(name, fields, input);
+                Ok(going)
+            }
+            Err(err) => {
+                // strict mode stops at a complaint, which still gets its ok:false object
+                if input.has_errors() {
+                    self.answer(name, Vec::new(), input);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    // one json line for the command: its name, the tree, whether it went through, the
+    // fields of a query and whatever else it had to say
+    fn answer(&mut self, command: &str, fields: Vec<(&str, JsonValue)>, input: &mut ReplInput) {
+        let errors = input.take_errors();
+        let mut object = vec![
+            ("command", JsonValue::String(command.to_string())),
+            ("tree", JsonValue::String(self.name.clone())),
+            ("ok", JsonValue::Bool(errors.is_empty())),
+        ];
+        object.extend(fields);
+        let strings = |lines: Vec<String>| JsonValue::Array(lines.into_iter().map(JsonValue::String).collect());
+        if !errors.is_empty() {
+            object.push(("errors", strings(errors)));
+        }
+        let said = self.said.as_ref().map(|said| said.take()).unwrap_or_default();
+        if !said.is_empty() {
+            object.push(("messages", strings(said)));
+        }
+        let object = JsonValue::object(object);
+        match &mut self.answers {
+            Some(answers) => answers.push(object),
+            None => println!("{}", object),
+        }
+    }
+
+    // the answer of a command that only looks at the tree, as json fields, None for the
+    // commands without one
+    fn query(&self, command: &Command, input: &mut ReplInput) -> Result<Option<Vec<(&'static str, JsonValue)>>, TreeError> {
+        let tree = &self.current.tree;
+        let keys = |keys: Vec<JsonValue>| JsonValue::Array(keys);
+        let fields = match command {
+            Command::Find(key) => match tree.find_json(key) {
+                Ok((found, path)) => {
+                    let depth = if found { JsonValue::number(path.len() - 1) } else { JsonValue::Null };
+                    vec![("key", self.keys.json_value(key)), ("found", JsonValue::Bool(found)), ("depth", depth), ("path", JsonValue::Array(path))]
+                }
+                Err(err) => {
+                    input.complain(&err.to_string())?;
+                    Vec::new()
+                }
+            },
+            Command::Range(low, high) => match tree.range_json(low, high) {
+                Ok(found) => vec![("low", self.keys.json_value(low)), ("high", self.keys.json_value(high)), ("keys", keys(found))],
+                Err(err) => {
+                    input.complain(&err.to_string())?;
+                    Vec::new()
+                }
+            },
+            Command::Print => vec![("shape", parse_json(&tree.to_json())?)],
+            Command::Height => vec![("height", JsonValue::number(tree.height()))],
+            Command::Leaves => vec![("leaves", JsonValue::number(tree.leaves()))],
+            Command::Size => vec![("nodes", JsonValue::number(tree.len()))],
+            Command::Empty => vec![("empty", JsonValue::Bool(tree.len() == 0))],
+            Command::InOrder => vec![("keys", keys(tree.keys_json(Walk::In)))],
+            Command::PreOrder => vec![("keys", keys(tree.keys_json(Walk::Pre)))],
+            Command::PostOrder => vec![("keys", keys(tree.keys_json(Walk::Post)))],
+            Command::Levels => vec![("levels", JsonValue::Array(tree.levels_json().into_iter().map(keys).collect()))],
+            _ => return Ok(None),
+        };
+        Ok(Some(fields))
+    }
+
+    // run one command with its text output, false once the session is over
+    fn perform(&mut self, command: Command, input: &mut ReplInput) -> Result<bool, TreeError> {
+        let edits_keys = matches!(command, Command::Insert(_) | Command::Delete(_) | Command::Fill(_) | Command::Undo(_) | Command::Redo(_));
+        let before = self.current.rotations();
+        match command {
+            Command::Insert(keys) => self.insert(keys, input)?,
+            Command::Delete(keys) => self.delete(keys, input)?,
+            Command::Fill(workload) => self.generate(workload, input)?,
+            Command::Find(key) => match self.current.tree.find_path(&key) {
+                Ok((true, path)) => {
+                    self.say(format!("{} is in the tree at depth {}", self.keys.show(&key), path.len() - 1));
+                    self.say(format!("path: {}", path.join(" → ")));
+                }
+                Ok((false, path)) => {
+                    self.say(format!("{} is not in the tree", self.keys.show(&key)));
+                    if !path.is_empty() {
+                        self.say(format!("searched: {}", path.join(" → ")));
+                    }
+                }
+                Err(err) => input.complain(&err.to_string())?,
+            },
+            Command::Range(low, high) => match self.current.tree.range_keys(&low, &high) {
+                Ok(keys) if keys.is_empty() => self.say(format!("no keys from {} to {}", self.keys.show(&low), self.keys.show(&high))),
+                Ok(keys) => self.say(format!("keys from {} to {}: {}", self.keys.show(&low), self.keys.show(&high), keys.join(" "))),
+                Err(err) => input.complain(&err.to_string())?,
+            },
+            Command::Undo(count) => self.undo(count, input)?,
+            Command::Redo(count) => self.redo(count, input)?,
+            Command::History => self.print_history(),
+            Command::Switch(kind) => self.switch(kind, input)?,
+            Command::New(kind, name) => self.new_named(kind, name, input)?,
+            Command::Use(name) => self.use_named(name, input)?,
+            Command::List => self.list(),
+            Command::Drop(name) => self.drop_named(name, input)?,
+            Command::Copy(from, to, kind) => self.copy(from, to, kind, input)?,
+            Command::Compare(first, second) => self.compare(&first, &second, input)?,
+            Command::Dual(on) => self.dual(on, input)?,
+            Command::Save(path) => self.save(&path, input)?,
+            Command::Load(path) => self.load(&path, input)?,
+            Command::Print => {
+                if self.current.tree.len() == 0 {
+                    self.say("the tree is empty");
+                } else {
+                    self.current.tree.print();
+                }
+            }
+            Command::Height => self.say(format!("tree height: {}", self.current.tree.height())),
+            Command::Leaves => self.say(format!("number of leaves: {}", self.current.tree.leaves())),
+            Command::Size => self.say(format!("number of nodes: {}", self.current.tree.len())),
+            Command::Empty => {
+                if self.current.tree.len() == 0 {
+                    self.say("the tree is empty");
+                } else {
+                    self.say("the tree is not empty");
+                }
+            }
+            Command::InOrder => self.say(format!("in-order: {}", self.current.tree.in_order().join(" "))),
+            Command::PreOrder => self.say(format!("pre-order: {}", self.current.tree.pre_order().join(" "))),
+            Command::PostOrder => self.say(format!("post-order: {}", self.current.tree.post_order().join(" "))),
+            Command::Levels => self.current.tree.print_levels(),
+            Command::Dot(path) => self.write_file(&path, self.current.tree.to_dot(), input)?,
+            Command::Json(path) => self.write_file(&path, self.current.tree.to_json(), input)?,
+            Command::Import(path) => self.import(&path, input)?,
+            Command::Svg(path) => self.write_file(&path, self.current.tree.to_svg(), input)?,
+            // the steps are printed as they happen, they have no place in a json object
+            Command::Explain(on) if self.said.is_some() && on != Some(false) => input.complain("explain mode is not available with --json")?,
+            Command::Explain(on) => {
+                self.explain.set(on.unwrap_or(!self.explain.get()));
+                self.say(format!("explain mode is {}", if self.explain.get() { "on" } else { "off" }));
+            }
+            Command::Stats => {
+                self.say(format!("last operation: {}", self.current.tree.last_stats()));
+                self.say(format!("total: {}", self.current.tree.total_stats()));
+            }
+            Command::Help => HELP.lines().for_each(|line| self.say(line)),
+            Command::Quit => {
+                self.say("exited");
+                return Ok(false);
+            }
+        }
+        if edits_keys && self.current.mirror.is_some() {
+            self.say("");
+            self.print_dual(before);
+        }
+        Ok(true)
+    }
+}
+
+// the interactive driver on stdin
+pub fn run() -> Result<(), TreeError> {
+    run_repl(None, false, KeyType::I64, false)
+}
+
+// the session fed from a script file or from stdin, strict stops at the first failing command,
+// json answers every command with one json object per line and prints nothing else
+pub fn run_repl(script: Option<&str>, strict: bool, keys: KeyType, json: bool) -> Result<(), TreeError> {
+    let mut input = match script {
+        Some(path) => ReplInput::script(path, strict)?,
+        None => ReplInput::stdin(strict),
+    };
+    let mut session = Session::new(TreeKind::Rb, keys, input.is_interactive(), json);
+    if json {
+        input.keep_errors();
+    } else {
+        println!("using the {} tree {} with {} keys, type help for the commands", type_name(session.current.tree.kind()), session.name, keys.name());
+    }
+
+    loop {
+        if input.is_interactive() && !json {
+            print!("{} ({})> ", session.name, session.current.tree.kind().name());
+            io::stdout().flush()?;
+        }
+        let line = input.next_line();
+        // a bad directive on the way gets an object of its own
+        if json && input.has_errors() {
+            session.answer("directive", Vec::new(), &mut input);
+        }
+        let line = match line? {
+            Some(line) => line,
+            None => break, // end of input
+        };
+        match parse_command(&line) {
+            Ok(Some(command)) => {
+                if !session.execute(command, &mut input)? {
+                    break;
+                }
+            }
+            Ok(None) => {}
+            Err(message) => {
+                let complained = input.complain(&message);
+                if json {
+                    session.answer(line.split_whitespace().next().unwrap_or_default(), Vec::new(), &mut input);
+                }
+                complained?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a session that keeps what it says, and complaints, instead of printing them
+    fn session(keys: KeyType) -> (Session, ReplInput) {
+        let mut input = ReplInput::stdin(false);
+        input.keep_errors();
+        let mut session = Session::new(TreeKind::Rb, keys, false, true);
+        session.answers = Some(Vec::new());
+        (session, input)
+    }
+
+    // run one command line the way --json does, the object it was answered with
+    fn answer(session: &mut Session, input: &mut ReplInput, line: &str) -> JsonValue {
+        let command = parse_command(line).unwrap().unwrap();
+        let _ = session.execute(command, input);
+        let mut answers = session.answers.replace(Vec::new()).unwrap();
+        assert_eq!(answers.len(), 1, "{}", line);
+        answers.remove(0)
+    }
+
+    // run one command line, what it said followed by what it complained about
+    fn run(session: &mut Session, input: &mut ReplInput, line: &str) -> Vec<String> {
+        let command = parse_command(line).unwrap().unwrap();
+        session.perform(command, input).unwrap();
+        let mut said = session.said.as_ref().unwrap().take();
+        said.extend(input.take_errors());
+        said
+    }
+
+    #[test]
+    fn keys_with_quotes_backslashes_and_spaces_are_carried_over_as_they_are() {
+        let (mut session, mut input) = session(KeyType::String);
+        run(&mut session, &mut input, r#"insert a"b c\d "new york""#);
+        let keys = session.current.tree.in_order();
+        assert_eq!(keys, [r#""a\"b""#, r#""c\\d""#, r#""new york""#]);
+
+        run(&mut session, &mut input, "switch avl");
+        assert_eq!(session.current.tree.in_order(), keys);
+        run(&mut session, &mut input, "dual on");
+        assert_eq!(session.current.mirror.as_ref().unwrap().in_order(), keys);
+        run(&mut session, &mut input, "copy main other rb");
+        assert_eq!(session.others["other"].tree.in_order(), keys);
+        assert_eq!(run(&mut session, &mut input, "compare main other")[0], "main and other hold the same 3 keys");
+
+        run(&mut session, &mut input, "use other");
+        run(&mut session, &mut input, r#"delete a"b"#);
+        let said = run(&mut session, &mut input, "compare main other");
+        assert_eq!(said[..3], ["2 keys in both", r#"only in main: "a\"b""#, "only in other: -"]);
+    }
+
+    #[test]
+    fn folded_keys_compare_without_case() {
+        let (mut session, mut input) = session(KeyType::Folded);
+        run(&mut session, &mut input, "insert Apple pear");
+        run(&mut session, &mut input, "new avl other");
+        run(&mut session, &mut input, "insert apple fig");
+        let said = run(&mut session, &mut input, "compare main other");
+        assert_eq!(said[..3], ["1 keys in both", r#"only in main: "pear""#, r#"only in other: "fig""#]);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let (mut session, mut input) = session(KeyType::I64);
+        run(&mut session, &mut input, "insert 5 3 9");
+        run(&mut session, &mut input, "delete 3");
+        run(&mut session, &mut input, "insert 7");
+        assert_eq!(run(&mut session, &mut input, "undo 2"), ["undid insert 7", "undid delete 3"]);
+        assert_eq!(session.current.tree.in_order(), ["3", "5", "9"]);
+        assert_eq!(run(&mut session, &mut input, "history"), ["history of main, oldest first:", "   1  insert 5 3 9", "   2  delete 3  (undone)", "   3  insert 7  (undone)"]);
+
+        assert_eq!(run(&mut session, &mut input, "redo"), ["redid delete 3"]);
+        assert_eq!(session.current.tree.in_order(), ["5", "9"]);
+        assert_eq!(run(&mut session, &mut input, "undo 3"), ["undid delete 3", "undid insert 5 3 9", "nothing more to undo"]);
+        assert_eq!(session.current.tree.len(), 0);
+        assert_eq!(run(&mut session, &mut input, "redo 4"), ["redid insert 5 3 9", "redid delete 3", "redid insert 7", "nothing more to redo"]);
+        assert_eq!(session.current.tree.in_order(), ["5", "7", "9"]);
+    }
+
+    #[test]
+    fn a_new_edit_clears_what_was_undone() {
+        let (mut session, mut input) = session(KeyType::I64);
+        run(&mut session, &mut input, "insert 1");
+        run(&mut session, &mut input, "insert 2");
+        run(&mut session, &mut input, "undo");
+        run(&mut session, &mut input, "insert 3");
+        assert_eq!(run(&mut session, &mut input, "redo"), ["nothing more to redo"]);
+        assert_eq!(session.current.tree.in_order(), ["1", "3"]);
+        // an edit that changes nothing is not one
+        run(&mut session, &mut input, "undo");
+        run(&mut session, &mut input, "insert 1");
+        assert_eq!(run(&mut session, &mut input, "redo"), ["redid insert 3"]);
+    }
+
+    #[test]
+    fn history_forgets_the_oldest_commands_past_its_limit() {
+        let (mut session, mut input) = session(KeyType::I64);
+        for key in 0..HISTORY_LIMIT + 20 {
+            run(&mut session, &mut input, &format!("insert {}", key));
+        }
+        let said = run(&mut session, &mut input, &format!("undo {}", HISTORY_LIMIT + 1));
+        assert_eq!(said.len(), HISTORY_LIMIT + 1);
+        assert_eq!(said[HISTORY_LIMIT - 1], "undid insert 20");
+        assert_eq!(said[HISTORY_LIMIT], "nothing more to undo");
+        // the oldest commands cannot be taken back any more
+        assert_eq!(session.current.tree.in_order(), (0..20).map(|key| key.to_string()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn find_shows_the_path_with_colours_or_heights() {
+        let (mut session, mut input) = session(KeyType::I64);
+        assert_eq!(run(&mut session, &mut input, "find 4"), ["4 is not in the tree"]);
+        run(&mut session, &mut input, "insert 5 3 9 1");
+        assert_eq!(run(&mut session, &mut input, "find 5"), ["5 is in the tree at depth 0", "path: 5 black"]);
+        assert_eq!(run(&mut session, &mut input, "find 1"), ["1 is in the tree at depth 2", "path: 5 black → 3 black → 1 red"]);
+        assert_eq!(run(&mut session, &mut input, "find 4"), ["4 is not in the tree", "searched: 5 black → 3 black"]);
+        assert_eq!(run(&mut session, &mut input, "find four"), ["'four' is not a valid key"]);
+
+        // the keys go over in order, which makes 3 the root
+        run(&mut session, &mut input, "switch avl");
+        assert_eq!(run(&mut session, &mut input, "find 1"), ["1 is in the tree at depth 1", "path: 3 (height 3) → 1 (height 1)"]);
+        assert_eq!(run(&mut session, &mut input, "find 10"), ["10 is not in the tree", "searched: 3 (height 3) → 5 (height 2) → 9 (height 1)"]);
+    }
+
+    #[test]
+    fn find_answers_in_json_with_a_null_depth_when_missing() {
+        let (mut session, mut input) = session(KeyType::String);
+        run(&mut session, &mut input, "insert m c x");
+        let found = answer(&mut session, &mut input, "find c");
+        assert_eq!(found.to_string(), r#"{"command":"find","tree":"main","ok":true,"key":"c","found":true,"depth":1,"path":[{"key":"m","colour":"black"},{"key":"c","colour":"red"}]}"#);
+        let missing = answer(&mut session, &mut input, "find d");
+        assert_eq!(missing.get("found"), Some(&JsonValue::Bool(false)));
+        assert_eq!(missing.get("depth"), Some(&JsonValue::Null));
+        assert_eq!(missing.get("path").unwrap().to_string(), r#"[{"key":"m","colour":"black"},{"key":"c","colour":"red"}]"#);
+
+        run(&mut session, &mut input, "switch avl");
+        let found = answer(&mut session, &mut input, "find x");
+        assert_eq!(found.get("depth"), Some(&JsonValue::number(1)));
+        assert_eq!(found.get("path").unwrap().to_string(), r#"[{"key":"m","height":2},{"key":"x","height":1}]"#);
+    }
+
+    #[test]
+    fn dual_mode_compares_the_tree_with_its_mirror() {
+        let (mut session, mut input) = session(KeyType::I64);
+        run(&mut session, &mut input, "insert 1 2 3");
+        let said = run(&mut session, &mut input, "dual on");
+        assert_eq!(
+            said[..6],
+            [
+                "dual mode is on for main, inserts and deletes also go to an avl mirror",
+                "                          rb     avl",
+                "height                     2       2",
+                "leaves                     2       2",
+                "rotations                  0       0",
+                "rotations in total         1       1",
+            ]
+        );
+        assert_eq!(said[7], "main (rb)             mirror (avl)");
+
+        // the sorted keys make both trees rotate, the table counts this command alone
+        let said = run(&mut session, &mut input, "insert 4 5 6 7");
+        assert_eq!(said[..7], ["inserted 4 5 6 7", "", "                          rb     avl", "height                     4       3", "leaves                     4       4", "rotations                  2       3", "rotations in total         3       4"]);
+        assert_eq!(session.current.mirror.as_ref().unwrap().in_order(), session.current.tree.in_order());
+        assert_eq!(run(&mut session, &mut input, "height"), ["tree height: 4"]);
+    }
+
+    #[test]
+    fn the_mirror_follows_every_edit() {
+        let (mut session, mut input) = session(KeyType::I64);
+        run(&mut session, &mut input, "dual on");
+        let mirror_keys = |session: &Session| session.current.mirror.as_ref().unwrap().in_order();
+        for line in ["insert 8 4 12 2 6", "delete 4", "sequence zigzag 5", "undo", "undo", "redo"] {
+            run(&mut session, &mut input, line);
+            assert_eq!(mirror_keys(&session), session.current.tree.in_order(), "{}", line);
+        }
+        assert_eq!(mirror_keys(&session), ["2", "6", "8", "12"]);
+
+        // a switch swaps the kinds around, the mirror is always the other one
+        run(&mut session, &mut input, "switch avl");
+        assert_eq!(session.current.mirror.as_ref().unwrap().kind(), TreeKind::Rb);
+        assert_eq!(mirror_keys(&session), ["2", "6", "8", "12"]);
+        // a failed insert reaches neither tree
+        assert_eq!(run(&mut session, &mut input, "insert 6").last().unwrap(), "6 is already in the tree");
+        assert_eq!(mirror_keys(&session).len(), 4);
+
+        assert_eq!(run(&mut session, &mut input, "dual off"), ["dual mode is off for main"]);
+        assert!(session.current.mirror.is_none());
+        assert_eq!(run(&mut session, &mut input, "insert 1"), ["inserted 1"]);
+    }
+
+    #[test]
+    fn json_objects_carry_the_fields_of_each_command() {
+        let (mut session, mut input) = session(KeyType::I64);
+        let mut json = |line: &str| answer(&mut session, &mut input, line).to_string();
+        assert_eq!(json("insert 5 3 9 1"), r#"{"command":"insert","tree":"main","ok":true,"messages":["inserted 5 3 9 1"]}"#);
+        assert_eq!(json("insert 3 7"), r#"{"command":"insert","tree":"main","ok":false,"errors":["3 is already in the tree"],"messages":["inserted 7"]}"#);
+        assert_eq!(json("delete 4"), r#"{"command":"delete","tree":"main","ok":false,"errors":["4 is not in the tree"]}"#);
+        assert_eq!(json("inorder"), r#"{"command":"inorder","tree":"main","ok":true,"keys":[1,3,5,7,9]}"#);
+        assert_eq!(json("preorder"), r#"{"command":"preorder","tree":"main","ok":true,"keys":[5,3,1,9,7]}"#);
+        assert_eq!(json("postorder"), r#"{"command":"postorder","tree":"main","ok":true,"keys":[1,3,7,9,5]}"#);
+        assert_eq!(json("levels"), r#"{"command":"levels","tree":"main","ok":true,"levels":[[5],[3,9],[1,7]]}"#);
+        assert_eq!(json("range 2 7"), r#"{"command":"range","tree":"main","ok":true,"low":2,"high":7,"keys":[3,5,7]}"#);
+        assert_eq!(json("height"), r#"{"command":"height","tree":"main","ok":true,"height":3}"#);
+        assert_eq!(json("leaves"), r#"{"command":"leaves","tree":"main","ok":true,"leaves":2}"#);
+        assert_eq!(json("size"), r#"{"command":"size","tree":"main","ok":true,"nodes":5}"#);
+        assert_eq!(json("empty"), r#"{"command":"empty","tree":"main","ok":true,"empty":false}"#);
+        assert_eq!(json("range 2 x"), r#"{"command":"range","tree":"main","ok":false,"errors":["'x' is not a valid key"]}"#);
+        assert_eq!(json("explain on"), r#"{"command":"explain","tree":"main","ok":false,"errors":["explain mode is not available with --json"]}"#);
+        // the tree field names the tree in use once the command is done
+        assert_eq!(json("new avl other"), r#"{"command":"new","tree":"other","ok":true,"messages":["using the new AVL tree other"]}"#);
+        assert_eq!(json("print"), r#"{"command":"print","tree":"other","ok":true,"shape":{"tree":"avl","root":null}}"#);
+        assert_eq!(json("use main"), r#"{"command":"use","tree":"main","ok":true,"messages":["using the red-black tree main with 5 keys"]}"#);
+    }
+
+    #[test]
+    fn json_keys_follow_the_key_type() {
+        let (mut session, mut input) = session(KeyType::String);
+        let mut json = |line: &str| answer(&mut session, &mut input, line).to_string();
+        json(r#"insert pear "new york" 10"#);
+        assert_eq!(json("inorder"), r#"{"command":"inorder","tree":"main","ok":true,"keys":["10","new york","pear"]}"#);
+        assert_eq!(json("levels"), r#"{"command":"levels","tree":"main","ok":true,"levels":[["new york"],["10","pear"]]}"#);
+    }
+
+    #[test]
+    fn a_strict_failure_still_gets_its_object() {
+        let (mut session, _) = session(KeyType::I64);
+        let mut input = ReplInput::reader("run.txt", "#strict on\n".as_bytes(), false);
+        input.keep_errors();
+        assert_eq!(input.next_line().unwrap(), None);
+        let command = parse_command("delete 1").unwrap().unwrap();
+        assert_eq!(session.execute(command, &mut input).unwrap_err().to_string(), "command failed: run.txt:1: 1 is not in the tree");
+        let answers = session.answers.take().unwrap();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].to_string(), r#"{"command":"delete","tree":"main","ok":false,"errors":["1 is not in the tree"]}"#);
+    }
+}
@@ -0,0 +1,347 @@
+// the repl command language: one command per line, a word followed by its arguments
+//
+//   insert 4 8 15     delete 8     find 15     range 3 10     switch avl
+//   random 1000 --seed 42 --range 0..10000     sequence zigzag 500
+//   new avl orders    use orders   copy orders backup          compare orders backup
+//
+// keys stay text here, the tree they are meant for decides whether they are valid
+
+use crate::dyntree::TreeKind;
+use crate::workload::{parse_workload, Workload};
+
+pub const HELP: &str = "\
+commands:
+  insert KEY...         insert the keys, string keys with spaces go in double quotes:
+                        insert \"new york\" paris
+  delete KEY...         delete the keys
+  find KEY              say whether the key is in the tree, with its depth and the
+                        path from the root (colours for rb, heights for avl)
+  range LOW HIGH        list the keys from LOW to HIGH, both included
+  random COUNT [--seed N] [--range LOW..HIGH]
+                        insert COUNT distinct random keys from LOW up to but not
+                        including HIGH (default 0..10*COUNT), the same seed gives the
+                        same keys, without one a seed is picked and shown
+  sequence sorted|reverse|zigzag COUNT
+                        insert 0 to COUNT-1 in that order, zigzag alternates between
+                        the smallest and the largest keys left
+  undo [COUNT]          take back the last insert or delete command, or COUNT of them
+  redo [COUNT]          bring back what undo took back, until the next insert or delete
+  history               list the inserts and deletes undo can take back, the last
+                        100 of them are kept for each tree
+  switch rb|avl         move the keys into a tree of the other type
+  new rb|avl NAME       start an empty tree called NAME and use it
+  use NAME              carry on with the tree called NAME
+  list                  list the trees in the session
+  drop NAME             throw the tree called NAME away
+  copy FROM TO [rb|avl] copy the keys of FROM into a new tree TO, of the same type
+                        unless one is given
+  compare NAME NAME     show the keys the two trees do not share, and their shapes
+  dual [on|off]         mirror every insert and delete into a tree of the other type
+                        and print both side by side, without a word it toggles
+  print                 print the tree with its structure
+  height                print the height
+  leaves                print the number of leaves
+  size                  print the number of nodes
+  empty                 say whether the tree is empty
+  inorder, preorder, postorder, levels
+                        print the keys in that order
+  dot [PATH]            write a graphviz dot file (default tree.dot)
+  json [PATH]           write a json shape file (default tree.json)
+  import [PATH]         replace the tree with a json shape file (default tree.json)
+  svg [PATH]            write an svg drawing (default tree.svg)
+  save [PATH]           write every tree of the session to PATH (default session.dtree)
+  load [PATH]           replace the session with the trees in PATH (default session.dtree)
+  explain [on|off]      show every rebalancing step, without a word it toggles
+  stats                 comparisons, rotations and the like
+  help                  show this text
+  quit                  leave, as does the end of the input
+
+started with --json, every command answers with one json object per line instead, the
+fields are listed in datatrees --help
+";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Insert(Vec<String>),
+    Delete(Vec<String>),
+    Find(String),
+    Range(String, String),
+    Fill(Workload), // random or sequence
+    Undo(usize),
+    Redo(usize),
+    History,
+    Switch(TreeKind),
+    New(TreeKind, String),
+    Use(String),
+    List,
+    Drop(String),
+    Copy(String, String, Option<TreeKind>),
+    Compare(String, String),
+    Dual(Option<bool>), // None toggles
+    Print,
+    Height,
+    Leaves,
+    Size,
+    Empty,
+    InOrder,
+    PreOrder,
+    PostOrder,
+    Levels,
+    Dot(String),
+    Json(String),
+    Import(String),
+    Svg(String),
+    Save(String),
+    Load(String),
+    Explain(Option<bool>), // None toggles
+    Stats,
+    Help,
+    Quit,
+}
+
+impl Command {
+    // the word the command is typed with, the json output reports it under this name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Insert(_) => "insert",
+            Command::Delete(_) => "delete",
+            Command::Find(_) => "find",
+            Command::Range(_, _) => "range",
+            Command::Fill(Workload::Random { .. }) => "random",
+            Command::Fill(Workload::Sequence { .. }) => "sequence",
+            Command::Undo(_) => "undo",
+            Command::Redo(_) => "redo",
+            Command::History => "history",
+            Command::Switch(_) => "switch",
+            Command::New(_, _) => "new",
+            Command::Use(_) => "use",
+            Command::List => "list",
+            Command::Drop(_) => "drop",
+            Command::Copy(_, _, _) => "copy",
+            Command::Compare(_, _) => "compare",
+            Command::Dual(_) => "dual",
+            Command::Print => "print",
+            Command::Height => "height",
+            Command::Leaves => "leaves",
+            Command::Size => "size",
+            Command::Empty => "empty",
+            Command::InOrder => "inorder",
+            Command::PreOrder => "preorder",
+            Command::PostOrder => "postorder",
+            Command::Levels => "levels",
+            Command::Dot(_) => "dot",
+            Command::Json(_) => "json",
+            Command::Import(_) => "import",
+            Command::Svg(_) => "svg",
+            Command::Save(_) => "save",
+            Command::Load(_) => "load",
+            Command::Explain(_) => "explain",
+            Command::Stats => "stats",
+            Command::Help => "help",
+            Command::Quit => "quit",
+        }
+    }
+}
+
+fn tree_kind(name: &str) -> Result<TreeKind, String> {
+    TreeKind::from_name(name).ok_or(format!("unknown tree type '{}', expected rb or avl", name))
+}
+
+// on, off or nothing for a toggle
+fn on_off(args: &[String]) -> Option<Option<bool>> {
+    match args {
+        [] => Some(None),
+        [word] if word == "on" => Some(Some(true)),
+        [word] if word == "off" => Some(Some(false)),
+        _ => None,
+    }
+}
+
+// an optional count of at least one, one if it is left out
+fn count(args: &[String]) -> Option<usize> {
+    match args {
+        [] => Some(1),
+        [count] => count.parse::<usize>().ok().filter(|count| *count > 0),
+        _ => None,
+    }
+}
+
+// the words of a line split at whitespace, except inside double quotes so "new york" stays
+// one word; the quotes are kept for the string keys to take off
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let end = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(close) => close + 2,
+                None => return Err(format!("no closing quote after {}", rest)),
+            },
+            None => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+        let (word, after) = rest.split_at(end);
+        if !after.is_empty() && !after.starts_with(char::is_whitespace) {
+            return Err(format!("{} runs on after its closing quote", word));
+        }
+        words.push(word.to_string());
+        rest = after.trim_start();
+    }
+    Ok(words)
+}
+
+// turn one line into a command, None for a blank line, errors are meant for the user
+pub fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let mut words = split_words(line)?.into_iter();
+    let name = match words.next() {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let name = name.as_str();
+    let args: Vec<String> = words.collect();
+
+    // the checks on the number of arguments, with the usage line to show when they fail
+    let none = |command: Command| {
+        if args.is_empty() { Ok(command) } else { Err(format!("{} takes no arguments", name)) }
+    };
+    let some_keys = |usage: &str| {
+        if args.is_empty() { Err(format!("{} needs at least one key, e.g. {}", name, usage)) } else { Ok(args.clone()) }
+    };
+    let exactly = |count: usize, usage: &str| {
+        if args.len() == count { Ok(args.clone()) } else { Err(format!("usage: {}", usage)) }
+    };
+    let path = |default: &str| match args.as_slice() {
+        [] => Ok(default.to_string()),
+        [path] => Ok(path.clone()),
+        _ => Err(format!("usage: {} [PATH]", name)),
+    };
+
+    let command = match name {
+        "insert" => Command::Insert(some_keys("insert 4 8 15")?),
+        "delete" => Command::Delete(some_keys("delete 8")?),
+        "find" => Command::Find(exactly(1, "find KEY")?.remove(0)),
+        "range" => {
+            let mut bounds = exactly(2, "range LOW HIGH")?;
+            let high = bounds.remove(1);
+            Command::Range(bounds.remove(0), high)
+        }
+        "random" | "sequence" => {
+            let words: Vec<String> = std::iter::once(name.to_string()).chain(args.iter().cloned()).collect();
+            Command::Fill(parse_workload(&words)?)
+        }
+        "undo" => Command::Undo(count(&args).ok_or("usage: undo [COUNT]")?),
+        "redo" => Command::Redo(count(&args).ok_or("usage: redo [COUNT]")?),
+        "history" => none(Command::History)?,
+        "switch" => Command::Switch(tree_kind(&exactly(1, "switch rb|avl")?[0])?),
+        "new" => {
+            let mut words = exactly(2, "new rb|avl NAME")?;
+            Command::New(tree_kind(&words[0])?, words.remove(1))
+        }
+        "use" => Command::Use(exactly(1, "use NAME")?.remove(0)),
+        "list" => none(Command::List)?,
+        "drop" => Command::Drop(exactly(1, "drop NAME")?.remove(0)),
+        "copy" => match args.as_slice() {
+            [from, to] => Command::Copy(from.clone(), to.clone(), None),
+            [from, to, kind] => Command::Copy(from.clone(), to.clone(), Some(tree_kind(kind)?)),
+            _ => return Err("usage: copy FROM TO [rb|avl]".to_string()),
+        },
+        "compare" => {
+            let mut names = exactly(2, "compare NAME NAME")?;
+            let second = names.remove(1);
+            Command::Compare(names.remove(0), second)
+        }
+        "print" => none(Command::Print)?,
+        "height" => none(Command::Height)?,
+        "leaves" => none(Command::Leaves)?,
+        "size" => none(Command::Size)?,
+        "empty" => none(Command::Empty)?,
+        "inorder" => none(Command::InOrder)?,
+        "preorder" => none(Command::PreOrder)?,
+        "postorder" => none(Command::PostOrder)?,
+        "levels" => none(Command::Levels)?,
+        "dot" => Command::Dot(path("tree.dot")?),
+        "json" => Command::Json(path("tree.json")?),
+        "import" => Command::Import(path("tree.json")?),
+        "svg" => Command::Svg(path("tree.svg")?),
+        "save" => Command::Save(path("session.dtree")?),
+        "load" => Command::Load(path("session.dtree")?),
+        "explain" => Command::Explain(on_off(&args).ok_or("usage: explain [on|off]")?),
+        "dual" => Command::Dual(on_off(&args).ok_or("usage: dual [on|off]")?),
+        "stats" => none(Command::Stats)?,
+        "help" => none(Command::Help)?,
+        "quit" | "exit" => none(Command::Quit)?,
+        other => return Err(format!("unknown command '{}', type help for the list", other)),
+    };
+    Ok(Some(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_command, Command};
+    use crate::dyntree::TreeKind;
+    use crate::keys::StringKey;
+
+    // the command a line stands for, the test fails if it is not one
+    fn parsed(line: &str) -> Command {
+        parse_command(line).unwrap().unwrap()
+    }
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn commands_and_their_arguments() {
+        assert_eq!(parse_command("   ").unwrap(), None);
+        assert_eq!(parsed("insert 4 8 15"), Command::Insert(words(&["4", "8", "15"])));
+        assert_eq!(parsed("  delete   8 "), Command::Delete(words(&["8"])));
+        assert_eq!(parsed("find 15"), Command::Find("15".to_string()));
+        assert_eq!(parsed("range 3 10"), Command::Range("3".to_string(), "10".to_string()));
+        assert_eq!(parsed("undo"), Command::Undo(1));
+        assert_eq!(parsed("redo 3"), Command::Redo(3));
+        assert_eq!(parsed("switch avl"), Command::Switch(TreeKind::Avl));
+        assert_eq!(parsed("new rb orders"), Command::New(TreeKind::Rb, "orders".to_string()));
+        assert_eq!(parsed("copy a b"), Command::Copy("a".to_string(), "b".to_string(), None));
+        assert_eq!(parsed("copy a b avl"), Command::Copy("a".to_string(), "b".to_string(), Some(TreeKind::Avl)));
+        assert_eq!(parsed("dot"), Command::Dot("tree.dot".to_string()));
+        assert_eq!(parsed("save backup.dtree"), Command::Save("backup.dtree".to_string()));
+        assert_eq!(parsed("explain"), Command::Explain(None));
+        assert_eq!(parsed("explain on"), Command::Explain(Some(true)));
+        assert_eq!(parsed("dual off"), Command::Dual(Some(false)));
+        assert_eq!(parsed("levels"), Command::Levels);
+        assert_eq!(parsed("exit"), Command::Quit);
+    }
+
+    #[test]
+    fn bad_lines_say_what_is_wrong() {
+        let error = |line: &str| parse_command(line).unwrap_err();
+        assert_eq!(error("frobnicate"), "unknown command 'frobnicate', type help for the list");
+        assert_eq!(error("insert"), "insert needs at least one key, e.g. insert 4 8 15");
+        assert_eq!(error("find"), "usage: find KEY");
+        assert_eq!(error("range 3"), "usage: range LOW HIGH");
+        assert_eq!(error("height 3"), "height takes no arguments");
+        assert_eq!(error("undo 0"), "usage: undo [COUNT]");
+        assert_eq!(error("switch btree"), "unknown tree type 'btree', expected rb or avl");
+        assert_eq!(error("copy a"), "usage: copy FROM TO [rb|avl]");
+        assert_eq!(error("explain maybe"), "usage: explain [on|off]");
+        assert_eq!(error("dot a b"), "usage: dot [PATH]");
+    }
+
+    #[test]
+    fn quoted_words_stay_whole() {
+        match parse_command("insert \"hello world\"  b \"\"") {
+            Ok(Some(Command::Insert(keys))) => assert_eq!(keys, ["\"hello world\"", "b", "\"\""]),
+            _ => panic!("not an insert"),
+        }
+        assert!(parse_command("insert \"hello world").is_err());
+        assert!(parse_command("insert \"a\"b").is_err());
+    }
+
+    #[test]
+    fn a_lone_quote_is_not_a_string_key() {
+        assert_eq!("\"a b\"".parse::<StringKey>().unwrap(), StringKey("a b".to_string()));
+        assert_eq!("a\"b".parse::<StringKey>().unwrap(), StringKey("a\"b".to_string()));
+        for text in ["\"", "\"a", "a\""] {
+            assert!(text.parse::<StringKey>().is_err(), "{}", text);
+        }
+    }
+}
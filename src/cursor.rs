@@ -0,0 +1,219 @@
+// cursors over the red-black tree
+// a cursor sits on a node and walks to its neighbours through the parent pointers, so a
+// scan never has to go back to the root; past either end it sits on an empty "ghost"
+// position, from which moving forward wraps to the first key and moving back to the last
+//
+// only the red-black tree has cursors: AVL nodes keep no parent pointer, so an AVL cursor
+// could not take a step without searching down from the root again; its range and
+// in-order iterators cover scanning it
+
+use std::fmt::Display;
+
+use crate::rb::{maximum_node, minimum_node, predecessor_node, successor_node};
+use crate::rb::{RBTreeFunctions, RedBlackTree, RedBlackTree_Op};
+use crate::TreeError;
+
+pub struct Cursor<'a, T> {
+    tree: &'a RedBlackTree<T>,
+    current: RedBlackTree_Op<T>,
+}
+
+pub struct CursorMut<'a, T> {
+    tree: &'a mut RedBlackTree<T>,
+    current: RedBlackTree_Op<T>,
+}
+
+// first node with a key of at least value
+fn lower_bound_node<T: Ord>(root: &RedBlackTree_Op<T>, value: &T) -> RedBlackTree_Op<T> {
+    let mut bound = None;
+    let mut current = root.clone();
+    while let Some(node) = current {
+        if node.borrow().key >= *value {
+            current = node.borrow().left.clone();
+            bound = Some(node);
+        } else {
+            current = node.borrow().right.clone();
+        }
+    }
+    bound
+}
+
+fn next_of<T>(root: &RedBlackTree_Op<T>, current: &RedBlackTree_Op<T>) -> RedBlackTree_Op<T> {
+    match current {
+        Some(node) => successor_node(node),
+        None => root.as_ref().map(minimum_node),
+    }
+}
+
+fn prev_of<T>(root: &RedBlackTree_Op<T>, current: &RedBlackTree_Op<T>) -> RedBlackTree_Op<T> {
+    match current {
+        Some(node) => predecessor_node(node),
+        None => root.as_ref().map(maximum_node),
+    }
+}
+
+impl<T: Clone + Ord + Display> RedBlackTree<T> {
+    // cursor on value, or on the ghost position if it is not in the tree
+    pub fn cursor_at(&self, value: &T) -> Cursor<'_, T> {
+        Cursor { current: self.root.find_node(value), tree: self }
+    }
+
+    // cursor on the first key that is not less than value
+    pub fn lower_bound(&self, value: &T) -> Cursor<'_, T> {
+        Cursor { current: lower_bound_node(&self.root, value), tree: self }
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor { current: self.root.as_ref().map(minimum_node), tree: self }
+    }
+
+    pub fn cursor_at_mut(&mut self, value: &T) -> CursorMut<'_, T> {
+        CursorMut { current: self.root.find_node(value), tree: self }
+    }
+
+    pub fn lower_bound_mut(&mut self, value: &T) -> CursorMut<'_, T> {
+        CursorMut { current: lower_bound_node(&self.root, value), tree: self }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { current: self.root.as_ref().map(minimum_node), tree: self }
+    }
+}
+
+impl<'a, T: Clone + Ord + Display> Cursor<'a, T> {
+    // key under the cursor, None on the ghost position
+    pub fn key(&self) -> Option<T> {
+        self.current.as_ref().map(|node| node.borrow().key.clone())
+    }
+
+    pub fn peek_next(&self) -> Option<T> {
+        next_of(&self.tree.root, &self.current).map(|node| node.borrow().key.clone())
+    }
+
+    pub fn peek_prev(&self) -> Option<T> {
+        prev_of(&self.tree.root, &self.current).map(|node| node.borrow().key.clone())
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = next_of(&self.tree.root, &self.current);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = prev_of(&self.tree.root, &self.current);
+    }
+}
+
+impl<'a, T: Clone + Ord + Display> CursorMut<'a, T> {
+    pub fn key(&self) -> Option<T> {
+        self.current.as_ref().map(|node| node.borrow().key.clone())
+    }
+
+    pub fn peek_next(&self) -> Option<T> {
+        next_of(&self.tree.root, &self.current).map(|node| node.borrow().key.clone())
+    }
+
+    pub fn peek_prev(&self) -> Option<T> {
+        prev_of(&self.tree.root, &self.current).map(|node| node.borrow().key.clone())
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = next_of(&self.tree.root, &self.current);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = prev_of(&self.tree.root, &self.current);
+    }
+
+    // read-only view at the same position
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor { tree: self.tree, current: self.current.clone() }
+    }
+
+    // remove the key under the cursor and rebalance, the cursor moves on to the next key
+    pub fn remove_current(&mut self) -> Result<Option<T>, TreeError> {
+        let node = match self.current.take() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        let key = node.borrow().key.clone();
+        let next = self.tree.root.delete_node(&node, &mut self.tree.observers);
+        self.tree.record_stats();
+        self.current = next?;
+        self.tree.len -= 1;
+        Ok(Some(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rb::RedBlackTree;
+
+    fn tree_of(keys: &[u32]) -> RedBlackTree<u32> {
+        let mut tree = RedBlackTree::new();
+        for key in keys {
+            tree.insert(*key).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn lower_bound_finds_the_first_key_not_below() {
+        let tree = tree_of(&[10, 20, 30]);
+        assert_eq!(tree.lower_bound(&0).key(), Some(10));
+        assert_eq!(tree.lower_bound(&15).key(), Some(20));
+        assert_eq!(tree.lower_bound(&20).key(), Some(20));
+        assert_eq!(tree.lower_bound(&31).key(), None);
+        assert_eq!(tree.cursor_at(&25).key(), None);
+    }
+
+    #[test]
+    fn moving_past_either_end_wraps_through_the_ghost() {
+        let tree = tree_of(&[10, 20, 30]);
+        let mut cursor = tree.cursor_front();
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.key(), None);
+        assert_eq!((cursor.peek_prev(), cursor.peek_next()), (Some(30), Some(10)));
+        cursor.move_prev();
+        assert_eq!(cursor.key(), Some(30));
+        cursor.move_next();
+        assert_eq!(cursor.key(), None);
+        cursor.move_next();
+        assert_eq!(cursor.key(), Some(10));
+
+        let empty = RedBlackTree::<u32>::new();
+        let mut cursor = empty.cursor_front();
+        cursor.move_next();
+        assert_eq!(cursor.key(), None);
+    }
+
+    #[test]
+    fn remove_current_moves_on_to_the_next_key() {
+        let mut tree = tree_of(&[10, 20, 30]);
+        let mut cursor = tree.cursor_at_mut(&20);
+        assert_eq!(cursor.remove_current().unwrap(), Some(20));
+        assert_eq!(cursor.key(), Some(30));
+        assert_eq!(cursor.remove_current().unwrap(), Some(30));
+        assert_eq!(cursor.key(), None);
+        assert_eq!(cursor.remove_current().unwrap(), None);
+        tree.validate().unwrap();
+        assert_eq!(tree.iter().collect::<Vec<u32>>(), vec![10]);
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn removing_while_scanning_keeps_the_tree_valid() {
+        let mut tree = tree_of(&(0..1000).collect::<Vec<u32>>());
+        let mut cursor = tree.cursor_front_mut();
+        while let Some(key) = cursor.key() {
+            if key % 3 == 0 {
+                assert_eq!(cursor.remove_current().unwrap(), Some(key));
+            } else {
+                cursor.move_next();
+            }
+        }
+        tree.validate().unwrap();
+        assert!(tree.iter().eq((0..1000).filter(|key| key % 3 != 0)));
+        assert_eq!(tree.len(), 666);
+    }
+}
@@ -0,0 +1,147 @@
+// graphviz export for both trees
+// single children get an invisible sibling so dot still draws them on the correct side
+
+use std::cell::RefCell;
+use std::fmt::{Display, Write};
+use std::rc::Rc;
+
+use crate::traversal::BinaryNode;
+#[cfg(feature = "avl")]
+use crate::avl::{height_avl, AVL_Tree, AVL_Tree_Node};
+#[cfg(feature = "rb")]
+use crate::rb::{RBTreeNode, RedBlackTree};
+use crate::RBTreeNodeColour;
+
+// quote a label for use inside a dot string
+pub fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// walk the tree and emit every node with the attributes returned by node_attrs
+fn write_dot<N: BinaryNode>(root: &Option<Rc<RefCell<N>>>, node_attrs: impl Fn(&N) -> String) -> String {
+    let mut dot = String::new();
+    dot.push_str("digraph tree {\n");
+    dot.push_str("    graph [ordering=out];\n");
+    dot.push_str("    node [shape=circle, style=filled, fontname=\"Helvetica\"];\n");
+
+    let mut next_id = 0;
+    // nodes still to emit, with the id they were given when their parent was emitted
+    let mut stack: Vec<(Rc<RefCell<N>>, usize)> = Vec::new();
+    if let Some(root) = root {
+        stack.push((root.clone(), next_id));
+        next_id += 1;
+    }
+    while let Some((node, id)) = stack.pop() {
+        let borrowed_node = node.borrow();
+        writeln!(dot, "    n{} [{}];", id, node_attrs(&borrowed_node)).unwrap();
+
+        let children = [borrowed_node.left(), borrowed_node.right()];
+        if children.iter().all(|child| child.is_none()) {
+            continue;
+        }
+        let mut pending = Vec::new();
+        for (side, child) in ["l", "r"].iter().zip(children) {
+            match child {
+                Some(child) => {
+                    writeln!(dot, "    n{} -> n{};", id, next_id).unwrap();
+                    pending.push((child, next_id));
+                    next_id += 1;
+                }
+                None => {
+                    writeln!(dot, "    n{}{} [label=\"\", style=invis];", id, side).unwrap();
+                    writeln!(dot, "    n{} -> n{}{} [style=invis];", id, id, side).unwrap();
+                }
+            }
+        }
+        // right goes on first so nodes are numbered left to right
+        stack.extend(pending.into_iter().rev());
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(feature = "rb")]
+impl<T: Clone + Display> RedBlackTree<T> {
+    // graphviz source with the nodes filled in their colour
+    pub fn to_dot(&self) -> String {
+        write_dot(&self.root, |node: &RBTreeNode<T>| {
+            let fill = match node.color {
+                RBTreeNodeColour::Red => "red",
+                RBTreeNodeColour::Black => "black",
+            };
+            format!("label=\"{}\", fillcolor={}, fontcolor=white", escape_dot(&node.key.to_string()), fill)
+        })
+    }
+}
+
+#[cfg(feature = "avl")]
+impl<T: Clone + Display> AVL_Tree<T> {
+    // graphviz source with the height and balancing factor under each value
+    pub fn to_dot(&self) -> String {
+        write_dot(&self.root, |node: &AVL_Tree_Node<T>| {
+            let balancing_factor = height_avl(&node.left) - height_avl(&node.right);
+            format!(
+                "label=\"{}\\nh={} bf={}\", shape=ellipse, fillcolor=lightblue",
+                escape_dot(&node.value.to_string()),
+                node.height,
+                balancing_factor
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn red_black_dot_keeps_a_lone_child_on_its_side() {
+        let mut tree = RedBlackTree::new();
+        for key in [2, 1, 3, 4] {
+            tree.insert(key).unwrap();
+        }
+        let expected = "\
+digraph tree {
+    graph [ordering=out];
+    node [shape=circle, style=filled, fontname=\"Helvetica\"];
+    n0 [label=\"2\", fillcolor=black, fontcolor=white];
+    n0 -> n1;
+    n0 -> n2;
+    n1 [label=\"1\", fillcolor=black, fontcolor=white];
+    n2 [label=\"3\", fillcolor=black, fontcolor=white];
+    n2l [label=\"\", style=invis];
+    n2 -> n2l [style=invis];
+    n2 -> n3;
+    n3 [label=\"4\", fillcolor=red, fontcolor=white];
+}
+";
+        assert_eq!(tree.to_dot(), expected);
+        assert_eq!(RedBlackTree::<i32>::new().to_dot().lines().count(), 4);
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn avl_dot_shows_heights_and_balance() {
+        let mut tree = AVL_Tree::new();
+        for key in [2, 1, 3, 4] {
+            tree.insert(key).unwrap();
+        }
+        let dot = tree.to_dot();
+        let nodes: Vec<&str> = dot.lines().filter(|line| line.contains("shape=ellipse")).map(str::trim).collect();
+        assert_eq!(
+            nodes,
+            [
+                "n0 [label=\"2\\nh=3 bf=-1\", shape=ellipse, fillcolor=lightblue];",
+                "n1 [label=\"1\\nh=1 bf=0\", shape=ellipse, fillcolor=lightblue];",
+                "n2 [label=\"3\\nh=2 bf=-1\", shape=ellipse, fillcolor=lightblue];",
+                "n3 [label=\"4\\nh=1 bf=0\", shape=ellipse, fillcolor=lightblue];",
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        assert_eq!(escape_dot(r#"say "hi" \o/"#), r#"say \"hi\" \\o/"#);
+    }
+}
@@ -0,0 +1,567 @@
+// either tree behind one object safe trait, with keys passed around as text, so the
+// command line and the repl can drive whichever tree and key type the user picked
+
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::Display;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::avl::AVL_Tree;
+use crate::explain::Explainer;
+use crate::json::{JsonKey, JsonValue};
+use crate::keys::{FloatKey, FoldedKey, StringKey};
+use crate::rb::{print_tree, write_tree, RedBlackTree};
+use crate::stats::TreeStats;
+use crate::traversal::print_levels;
+use crate::{RBTreeNodeColour, TreeError};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TreeKind {
+    Rb,
+    Avl,
+}
+
+impl TreeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TreeKind::Rb => "rb",
+            TreeKind::Avl => "avl",
+        }
+    }
+
+    pub fn other(&self) -> TreeKind {
+        match self {
+            TreeKind::Rb => TreeKind::Avl,
+            TreeKind::Avl => TreeKind::Rb,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TreeKind> {
+        match name {
+            "rb" => Some(TreeKind::Rb),
+            "avl" => Some(TreeKind::Avl),
+            _ => None,
+        }
+    }
+
+    // an empty tree of this kind holding keys of the given type
+    pub(crate) fn new_tree(&self, keys: KeyType) -> Box<dyn DynTree> {
+        match keys {
+            KeyType::I32 => self.new_tree_of::<i32>(),
+            KeyType::I64 => self.new_tree_of::<i64>(),
+            KeyType::U64 => self.new_tree_of::<u64>(),
+            KeyType::I128 => self.new_tree_of::<i128>(),
+            KeyType::F64 => self.new_tree_of::<FloatKey>(),
+            KeyType::String => self.new_tree_of::<StringKey>(),
+            KeyType::Folded => self.new_tree_of::<FoldedKey>(),
+        }
+    }
+
+    fn new_tree_of<K: Key>(&self) -> Box<dyn DynTree> {
+        match self {
+            TreeKind::Rb => Box::new(RedBlackTree::<K>::new()),
+            TreeKind::Avl => Box::new(AVL_Tree::<K>::new()),
+        }
+    }
+
+    // a tree of this kind filled with the keys
+    fn tree_holding<K: Key>(&self, keys: impl Iterator<Item = K>) -> Result<Box<dyn DynTree>, TreeError> {
+        match self {
+            TreeKind::Rb => {
+                let mut tree = RedBlackTree::<K>::new();
+                for key in keys {
+                    tree.insert(key)?;
+                }
+                Ok(Box::new(tree))
+            }
+            TreeKind::Avl => {
+                let mut tree = AVL_Tree::<K>::new();
+                for key in keys {
+                    tree.insert(key)?;
+                }
+                Ok(Box::new(tree))
+            }
+        }
+    }
+}
+
+// the type of the keys, one for every tree of a session, picked at startup
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyType {
+    I32,
+    I64,
+    U64,
+    I128,
+    F64,    // any float but NaN
+    String, // byte order
+    Folded, // strings in lower case order, "Apple" and "apple" are one key
+}
+
+impl KeyType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyType::I32 => "i32",
+            KeyType::I64 => "i64",
+            KeyType::U64 => "u64",
+            KeyType::I128 => "i128",
+            KeyType::F64 => "f64",
+            KeyType::String => "string",
+            KeyType::Folded => "folded",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<KeyType> {
+        match name {
+            "i32" => Some(KeyType::I32),
+            "i64" => Some(KeyType::I64),
+            "u64" => Some(KeyType::U64),
+            "i128" => Some(KeyType::I128),
+            "f64" => Some(KeyType::F64),
+            "string" => Some(KeyType::String),
+            "folded" => Some(KeyType::Folded),
+            _ => None,
+        }
+    }
+
+    // the smallest and the largest key of the integer types, as text
+    fn limits(&self) -> Option<(String, String)> {
+        match self {
+            KeyType::I32 => Some((i32::MIN.to_string(), i32::MAX.to_string())),
+            KeyType::I64 => Some((i64::MIN.to_string(), i64::MAX.to_string())),
+            KeyType::U64 => Some((u64::MIN.to_string(), u64::MAX.to_string())),
+            KeyType::I128 => Some((i128::MIN.to_string(), i128::MAX.to_string())),
+            KeyType::F64 | KeyType::String | KeyType::Folded => None,
+        }
+    }
+
+    // fails the way inserting the key into a tree of this type would
+    pub(crate) fn check(&self, key: &str) -> Result<(), TreeError> {
+        match self {
+            KeyType::I32 => parse_key::<i32>(key).map(|_| ()),
+            KeyType::I64 => parse_key::<i64>(key).map(|_| ()),
+            KeyType::U64 => parse_key::<u64>(key).map(|_| ()),
+            KeyType::I128 => parse_key::<i128>(key).map(|_| ()),
+            KeyType::F64 => parse_key::<FloatKey>(key).map(|_| ()),
+            KeyType::String => parse_key::<StringKey>(key).map(|_| ()),
+            KeyType::Folded => parse_key::<FoldedKey>(key).map(|_| ()),
+        }
+    }
+
+    // typed text the way the trees print it, e.g. quoted for strings, as it is if it is
+    // not a key at all
+    pub(crate) fn show(&self, key: &str) -> String {
+        let shown = match self {
+            KeyType::I32 => parse_key::<i32>(key).map(|key| key.to_string()),
+            KeyType::I64 => parse_key::<i64>(key).map(|key| key.to_string()),
+            KeyType::U64 => parse_key::<u64>(key).map(|key| key.to_string()),
+            KeyType::I128 => parse_key::<i128>(key).map(|key| key.to_string()),
+            KeyType::F64 => parse_key::<FloatKey>(key).map(|key| key.to_string()),
+            KeyType::String => parse_key::<StringKey>(key).map(|key| key.to_string()),
+            KeyType::Folded => parse_key::<FoldedKey>(key).map(|key| key.to_string()),
+        };
+        shown.unwrap_or_else(|_| key.to_string())
+    }
+
+    // typed text as the json value the shape files use for the key, a string if it is not
+    // a key at all
+    pub(crate) fn json_value(&self, key: &str) -> JsonValue {
+        let value = match self {
+            KeyType::I32 => parse_key::<i32>(key).map(|key| key.to_json()),
+            KeyType::I64 => parse_key::<i64>(key).map(|key| key.to_json()),
+            KeyType::U64 => parse_key::<u64>(key).map(|key| key.to_json()),
+            KeyType::I128 => parse_key::<i128>(key).map(|key| key.to_json()),
+            KeyType::F64 => parse_key::<FloatKey>(key).map(|key| key.to_json()),
+            KeyType::String => parse_key::<StringKey>(key).map(|key| key.to_json()),
+            KeyType::Folded => parse_key::<FoldedKey>(key).map(|key| key.to_json()),
+        };
+        value.unwrap_or_else(|_| JsonValue::String(key.to_string()))
+    }
+
+    // a number the type cannot hold gets its own error, anything else is not a key at all
+    fn rejection(&self, key: &str) -> TreeError {
+        let digits = key.strip_prefix(['-', '+']).unwrap_or(key);
+        if let Some((min, max)) = self.limits() {
+            if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                return TreeError::KeyOutOfRange(key.to_string(), format!("{} from {} to {}", self.name(), min, max));
+            }
+        }
+        TreeError::InvalidKey(key.to_string())
+    }
+}
+
+// what a tree needs from its keys to sit behind DynTree
+pub(crate) trait Key: Clone + Ord + Display + FromStr + JsonKey + 'static {
+    const TYPE: KeyType;
+}
+
+impl Key for i32 {
+    const TYPE: KeyType = KeyType::I32;
+}
+
+impl Key for i64 {
+    const TYPE: KeyType = KeyType::I64;
+}
+
+impl Key for u64 {
+    const TYPE: KeyType = KeyType::U64;
+}
+
+impl Key for i128 {
+    const TYPE: KeyType = KeyType::I128;
+}
+
+impl Key for FloatKey {
+    const TYPE: KeyType = KeyType::F64;
+}
+
+impl Key for StringKey {
+    const TYPE: KeyType = KeyType::String;
+}
+
+impl Key for FoldedKey {
+    const TYPE: KeyType = KeyType::Folded;
+}
+
+fn parse_key<K: Key>(key: &str) -> Result<K, TreeError> {
+    key.parse::<K>().map_err(|_| K::TYPE.rejection(key))
+}
+
+// whether a tree of either type holds the key, false if its keys are of another type
+fn holds<K: Key>(tree: &dyn DynTree, key: &K) -> bool {
+    if let Some(rb) = tree.as_any().downcast_ref::<RedBlackTree<K>>() {
+        return rb.contains(key);
+    }
+    if let Some(avl) = tree.as_any().downcast_ref::<AVL_Tree<K>>() {
+        return avl.contains(key);
+    }
+    false
+}
+
+// the depth first orders the trees can list their keys in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Walk {
+    In,
+    Pre,
+    Post,
+}
+
+// the keys of each depth as json arrays, root first
+fn json_levels<K: Key>(levels: impl Iterator<Item = (usize, K)>) -> Vec<Vec<JsonValue>> {
+    let mut rows: Vec<Vec<JsonValue>> = Vec::new();
+    for (depth, key) in levels {
+        if rows.len() <= depth {
+            rows.push(Vec::new());
+        }
+        rows[depth].push(key.to_json());
+    }
+    rows
+}
+
+// what the command line and the repl need from either tree
+pub(crate) trait DynTree {
+    fn kind(&self) -> TreeKind;
+    fn set_strict(&mut self, strict: bool);
+    fn add_explainer(&mut self, enabled: Rc<Cell<bool>>, pauses: bool);
+    fn insert_key(&mut self, key: &str) -> Result<bool, TreeError>;
+    fn delete_key(&mut self, key: &str) -> Result<bool, TreeError>;
+    // a tree of the given type with the same keys and no observers, the keys go across as
+    // they are rather than through their text
+    fn copy_as(&self, kind: TreeKind) -> Result<Box<dyn DynTree>, TreeError>;
+    // the keys other does not hold, compared as keys so folded Apple and apple are one,
+    // nothing is missing from a tree of another key type
+    fn missing_from(&self, other: &dyn DynTree) -> Vec<String>;
+    fn as_any(&self) -> &dyn Any;
+    // whether the key is there, and the nodes the search passed with their colour or height
+    fn find_path(&self, key: &str) -> Result<(bool, Vec<String>), TreeError>;
+    fn range_keys(&self, low: &str, high: &str) -> Result<Vec<String>, TreeError>;
+    // swap the contents for the tree in a json shape document, observers stay
+    fn load_json(&mut self, text: &str) -> Result<(), TreeError>;
+    fn last_stats(&self) -> TreeStats;
+    fn total_stats(&self) -> TreeStats;
+    fn print(&self);
+    // what print shows, as a string
+    fn picture(&self) -> String;
+    fn height(&self) -> u64;
+    fn leaves(&self) -> u64;
+    fn len(&self) -> usize;
+    fn in_order(&self) -> Vec<String>;
+    fn pre_order(&self) -> Vec<String>;
+    fn post_order(&self) -> Vec<String>;
+    fn print_levels(&self);
+    // the same answers for the json output, keys are json values as in the shape files
+    fn keys_json(&self, walk: Walk) -> Vec<JsonValue>;
+    fn levels_json(&self) -> Vec<Vec<JsonValue>>;
+    fn find_json(&self, key: &str) -> Result<(bool, Vec<JsonValue>), TreeError>;
+    fn range_json(&self, low: &str, high: &str) -> Result<Vec<JsonValue>, TreeError>;
+    fn to_dot(&self) -> String;
+    fn to_json(&self) -> String;
+    fn to_svg(&self) -> String;
+}
+
+impl<K: Key> DynTree for RedBlackTree<K> {
+    fn kind(&self) -> TreeKind {
+        TreeKind::Rb
+    }
+    fn set_strict(&mut self, strict: bool) {
+        RedBlackTree::set_strict(self, strict);
+    }
+    fn add_explainer(&mut self, enabled: Rc<Cell<bool>>, pauses: bool) {
+        self.add_observer(Box::new(Explainer::for_rb(enabled, pauses)));
+    }
+    fn insert_key(&mut self, key: &str) -> Result<bool, TreeError> {
+        self.insert(parse_key(key)?)
+    }
+    fn delete_key(&mut self, key: &str) -> Result<bool, TreeError> {
+        self.remove(parse_key(key)?)
+    }
+    fn copy_as(&self, kind: TreeKind) -> Result<Box<dyn DynTree>, TreeError> {
+        kind.tree_holding(self.iter())
+    }
+    fn missing_from(&self, other: &dyn DynTree) -> Vec<String> {
+        self.iter().filter(|key| !holds(other, key)).map(|key| key.to_string()).collect()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn find_path(&self, key: &str) -> Result<(bool, Vec<String>), TreeError> {
+        let value: K = parse_key(key)?;
+        let path = self.search_path(&value);
+        let found = path.last().is_some_and(|(last, _)| *last == value);
+        let colour_name = |colour: &RBTreeNodeColour| if *colour == RBTreeNodeColour::Red { "red" } else { "black" };
+        Ok((found, path.iter().map(|(key, colour)| format!("{} {}", key, colour_name(colour))).collect()))
+    }
+    fn range_keys(&self, low: &str, high: &str) -> Result<Vec<String>, TreeError> {
+        Ok(self.range(parse_key(low)?, parse_key(high)?).map(|key| key.to_string()).collect())
+    }
+    fn load_json(&mut self, text: &str) -> Result<(), TreeError> {
+        let mut loaded = RedBlackTree::<K>::from_json(text)?;
+        std::mem::swap(&mut self.root, &mut loaded.root);
+        self.len = loaded.len;
+        Ok(())
+    }
+    fn last_stats(&self) -> TreeStats {
+        RedBlackTree::last_stats(self)
+    }
+    fn total_stats(&self) -> TreeStats {
+        RedBlackTree::total_stats(self)
+    }
+    fn print(&self) {
+        print_tree(&self.root, 0);
+    }
+    fn picture(&self) -> String {
+        write_tree(&self.root)
+    }
+    fn height(&self) -> u64 {
+        RedBlackTree::height(self) as u64
+    }
+    fn leaves(&self) -> u64 {
+        self.count_leaves() as u64
+    }
+    fn len(&self) -> usize {
+        RedBlackTree::len(self)
+    }
+    fn in_order(&self) -> Vec<String> {
+        self.iter().map(|key| key.to_string()).collect()
+    }
+    fn pre_order(&self) -> Vec<String> {
+        RedBlackTree::pre_order(self).map(|key| key.to_string()).collect()
+    }
+    fn post_order(&self) -> Vec<String> {
+        RedBlackTree::post_order(self).map(|key| key.to_string()).collect()
+    }
+    fn print_levels(&self) {
+        print_levels(self.level_order());
+    }
+    fn keys_json(&self, walk: Walk) -> Vec<JsonValue> {
+        match walk {
+            Walk::In => self.iter().map(|key| key.to_json()).collect(),
+            Walk::Pre => RedBlackTree::pre_order(self).map(|key| key.to_json()).collect(),
+            Walk::Post => RedBlackTree::post_order(self).map(|key| key.to_json()).collect(),
+        }
+    }
+    fn levels_json(&self) -> Vec<Vec<JsonValue>> {
+        json_levels(self.level_order())
+    }
+    fn find_json(&self, key: &str) -> Result<(bool, Vec<JsonValue>), TreeError> {
+        let value: K = parse_key(key)?;
+        let path = self.search_path(&value);
+        let found = path.last().is_some_and(|(last, _)| *last == value);
+        let colour_name = |colour: &RBTreeNodeColour| if *colour == RBTreeNodeColour::Red { "red" } else { "black" };
+        let nodes = path.iter().map(|(key, colour)| JsonValue::object(vec![("key", key.to_json()), ("colour", JsonValue::String(colour_name(colour).to_string()))]));
+        Ok((found, nodes.collect()))
+    }
+    fn range_json(&self, low: &str, high: &str) -> Result<Vec<JsonValue>, TreeError> {
+        Ok(self.range(parse_key(low)?, parse_key(high)?).map(|key| key.to_json()).collect())
+    }
+    fn to_dot(&self) -> String {
+        RedBlackTree::to_dot(self)
+    }
+    fn to_json(&self) -> String {
+        RedBlackTree::to_json(self)
+    }
+    fn to_svg(&self) -> String {
+        RedBlackTree::to_svg(self)
+    }
+}
+
+impl<K: Key> DynTree for AVL_Tree<K> {
+    fn kind(&self) -> TreeKind {
+        TreeKind::Avl
+    }
+    fn set_strict(&mut self, strict: bool) {
+        AVL_Tree::set_strict(self, strict);
+    }
+    fn add_explainer(&mut self, enabled: Rc<Cell<bool>>, pauses: bool) {
+        self.add_observer(Box::new(Explainer::for_avl(enabled, pauses)));
+    }
+    fn insert_key(&mut self, key: &str) -> Result<bool, TreeError> {
+        self.insert(parse_key(key)?)
+    }
+    fn delete_key(&mut self, key: &str) -> Result<bool, TreeError> {
+        self.remove(parse_key(key)?)
+    }
+    fn copy_as(&self, kind: TreeKind) -> Result<Box<dyn DynTree>, TreeError> {
+        kind.tree_holding(self.iter())
+    }
+    fn missing_from(&self, other: &dyn DynTree) -> Vec<String> {
+        self.iter().filter(|key| !holds(other, key)).map(|key| key.to_string()).collect()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn find_path(&self, key: &str) -> Result<(bool, Vec<String>), TreeError> {
+        let value: K = parse_key(key)?;
+        let path = self.search_path(&value);
+        let found = path.last().is_some_and(|(last, _)| *last == value);
+        Ok((found, path.iter().map(|(key, height)| format!("{} (height {})", key, height)).collect()))
+    }
+    fn range_keys(&self, low: &str, high: &str) -> Result<Vec<String>, TreeError> {
+        Ok(self.range(parse_key(low)?, parse_key(high)?).map(|key| key.to_string()).collect())
+    }
+    fn load_json(&mut self, text: &str) -> Result<(), TreeError> {
+        let mut loaded = AVL_Tree::<K>::from_json(text)?;
+        std::mem::swap(&mut self.root, &mut loaded.root);
+        self.len = loaded.len;
+        Ok(())
+    }
+    fn last_stats(&self) -> TreeStats {
+        AVL_Tree::last_stats(self)
+    }
+    fn total_stats(&self) -> TreeStats {
+        AVL_Tree::total_stats(self)
+    }
+    fn print(&self) {
+        AVL_Tree::print_avl_tree(&self.root, "", false);
+    }
+    fn picture(&self) -> String {
+        AVL_Tree::write_avl_tree_picture(&self.root)
+    }
+    fn height(&self) -> u64 {
+        AVL_Tree::height(self) as u64
+    }
+    fn leaves(&self) -> u64 {
+        self.count_leaves() as u64
+    }
+    fn len(&self) -> usize {
+        AVL_Tree::len(self)
+    }
+    fn in_order(&self) -> Vec<String> {
+        self.iter().map(|key| key.to_string()).collect()
+    }
+    fn pre_order(&self) -> Vec<String> {
+        AVL_Tree::pre_order(self).map(|key| key.to_string()).collect()
+    }
+    fn post_order(&self) -> Vec<String> {
+        AVL_Tree::post_order(self).map(|key| key.to_string()).collect()
+    }
+    fn print_levels(&self) {
+        print_levels(self.level_order());
+    }
+    fn keys_json(&self, walk: Walk) -> Vec<JsonValue> {
+        match walk {
+            Walk::In => self.iter().map(|key| key.to_json()).collect(),
+            Walk::Pre => AVL_Tree::pre_order(self).map(|key| key.to_json()).collect(),
+            Walk::Post => AVL_Tree::post_order(self).map(|key| key.to_json()).collect(),
+        }
+    }
+    fn levels_json(&self) -> Vec<Vec<JsonValue>> {
+        json_levels(self.level_order())
+    }
+    fn find_json(&self, key: &str) -> Result<(bool, Vec<JsonValue>), TreeError> {
+        let value: K = parse_key(key)?;
+        let path = self.search_path(&value);
+        let found = path.last().is_some_and(|(last, _)| *last == value);
+        let nodes = path.iter().map(|(key, height)| JsonValue::object(vec![("key", key.to_json()), ("height", JsonValue::number(height))]));
+        Ok((found, nodes.collect()))
+    }
+    fn range_json(&self, low: &str, high: &str) -> Result<Vec<JsonValue>, TreeError> {
+        Ok(self.range(parse_key(low)?, parse_key(high)?).map(|key| key.to_json()).collect())
+    }
+    fn to_dot(&self) -> String {
+        AVL_Tree::to_dot(self)
+    }
+    fn to_json(&self) -> String {
+        AVL_Tree::to_json(self)
+    }
+    fn to_svg(&self) -> String {
+        AVL_Tree::to_svg(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn out_of_range(keys: KeyType, key: &str) -> String {
+        match keys.check(key) {
+            Err(TreeError::KeyOutOfRange(_, range)) => range,
+            other => panic!("{} as {}: {:?}", key, keys.name(), other),
+        }
+    }
+
+    #[test]
+    fn integers_past_their_bounds_are_out_of_range() {
+        for key in ["2147483647", "-2147483648", "+5"] {
+            assert!(KeyType::I32.check(key).is_ok(), "{}", key);
+        }
+        assert_eq!(out_of_range(KeyType::I32, "2147483648"), "i32 from -2147483648 to 2147483647");
+        assert_eq!(out_of_range(KeyType::I32, "-2147483649"), "i32 from -2147483648 to 2147483647");
+
+        assert!(KeyType::U64.check("18446744073709551615").is_ok());
+        assert_eq!(out_of_range(KeyType::U64, "18446744073709551616"), "u64 from 0 to 18446744073709551615");
+        assert_eq!(out_of_range(KeyType::U64, "-1"), "u64 from 0 to 18446744073709551615");
+
+        let (min, max) = (i128::MIN.to_string(), i128::MAX.to_string());
+        assert!(KeyType::I128.check(&min).is_ok() && KeyType::I128.check(&max).is_ok());
+        out_of_range(KeyType::I128, "170141183460469231731687303715884105728");
+        out_of_range(KeyType::I128, "-170141183460469231731687303715884105729");
+
+        // anything that is not a whole number is not a key at all
+        for key in ["1.5", "-", "12a", "0x10", ""] {
+            assert!(matches!(KeyType::I64.check(key), Err(TreeError::InvalidKey(_))), "{}", key);
+        }
+    }
+
+    #[test]
+    fn nan_and_lone_quotes_are_not_keys() {
+        assert!(matches!(KeyType::F64.check("NaN"), Err(TreeError::InvalidKey(_))));
+        assert!(KeyType::F64.check("-1e300").is_ok());
+        for keys in [KeyType::String, KeyType::Folded] {
+            assert!(matches!(keys.check("\"abc"), Err(TreeError::InvalidKey(_))));
+            assert!(matches!(keys.check("\""), Err(TreeError::InvalidKey(_))));
+            assert!(keys.check("\"a b\"").is_ok());
+        }
+    }
+
+    #[test]
+    fn a_refused_key_leaves_the_tree_alone() {
+        for kind in [TreeKind::Rb, TreeKind::Avl] {
+            let mut tree = kind.new_tree(KeyType::U64);
+            tree.insert_key("7").unwrap();
+            assert!(matches!(tree.insert_key("-7"), Err(TreeError::KeyOutOfRange(..))));
+            assert!(matches!(tree.delete_key("seven"), Err(TreeError::InvalidKey(_))));
+            assert_eq!(tree.in_order(), ["7"]);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// explain mode for the repl: after every rebalancing step the tree is printed together
+// with a sentence saying what just happened, then it waits for enter
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::io;
+use std::rc::Rc;
+
+use crate::observer::{BalanceCase, TreeEvent, TreeObserver};
+use crate::RBTreeNodeColour;
+
+pub struct Explainer<T> {
+    avl: bool,
+    enabled: Rc<Cell<bool>>, // shared with the repl so it can be switched on and off
+    pauses: bool, // wait for enter after each step, off when running a script
+    deleting: bool,
+    step: usize,
+    pending: Vec<TreeEvent<T>>, // events since the last finished step
+}
+
+impl<T> Explainer<T> {
+    pub fn for_rb(enabled: Rc<Cell<bool>>, pauses: bool) -> Self {
+        Self { avl: false, enabled, pauses, deleting: false, step: 0, pending: Vec::new() }
+    }
+
+    pub fn for_avl(enabled: Rc<Cell<bool>>, pauses: bool) -> Self {
+        Self { avl: true, enabled, pauses, deleting: false, step: 0, pending: Vec::new() }
+    }
+}
+
+fn case_name(case: BalanceCase) -> &'static str {
+    match case {
+        BalanceCase::LeftLeft => "LL",
+        BalanceCase::LeftRight => "LR",
+        BalanceCase::RightRight => "RR",
+        BalanceCase::RightLeft => "RL",
+    }
+}
+
+// the balance factor and the shape of the heavy side that lead to an avl rotation
+fn avl_case_sentence<T: Display>(case: BalanceCase, key: &T) -> String {
+    match case {
+        BalanceCase::LeftLeft => format!("balance factor +2 at {}, left child does not lean right → LL single right rotation", key),
+        BalanceCase::LeftRight => format!("balance factor +2 at {}, left child leans right → LR double rotation", key),
+        BalanceCase::RightRight => format!("balance factor −2 at {}, right child does not lean left → RR single left rotation", key),
+        BalanceCase::RightLeft => format!("balance factor −2 at {}, right child leans left → RL double rotation", key),
+    }
+}
+
+impl<T: Display> Explainer<T> {
+    // turn the events of one step into a sentence
+    fn describe(&self) -> String {
+        let mut opening = None;
+        let mut case = None;
+        let mut rotations = Vec::new();
+        let mut recolours = Vec::new();
+        for event in &self.pending {
+            match event {
+                TreeEvent::NodeCreated { key } => {
+                    opening = Some(if self.avl { format!("inserted {} as a leaf", key) } else { format!("inserted {} as a red leaf", key) });
+                }
+                TreeEvent::NodeRemoved { key } => opening = Some(format!("removed {}", key)),
+                TreeEvent::Rebalance { case: balance_case, key } => {
+                    case = Some(if self.avl {
+                        avl_case_sentence(*balance_case, key)
+                    } else {
+                        format!("red child under a red parent with a black uncle, {} case at {}", case_name(*balance_case), key)
+                    });
+                }
+                TreeEvent::RotateLeft { node, pivot } => rotations.push(format!("rotate left at {} ({} moves up)", node, pivot)),
+                TreeEvent::RotateRight { node, pivot } => rotations.push(format!("rotate right at {} ({} moves up)", node, pivot)),
+                TreeEvent::Recolour { key, colour } => {
+                    let colour = if *colour == RBTreeNodeColour::Red { "red" } else { "black" };
+                    recolours.push(format!("{} {}", key, colour));
+                }
+                TreeEvent::HeightUpdated { .. } | TreeEvent::Step { .. } => {}
+            }
+        }
+
+        let mut actions = rotations;
+        if !recolours.is_empty() {
+            actions.push(format!("recolour {}", recolours.join(", ")));
+        }
+        if let Some(opening) = opening {
+            return opening;
+        }
+        if let Some(case) = case {
+            if self.avl {
+                return case;
+            }
+            return format!("{} → {}", case, actions.join(", then "));
+        }
+        if self.deleting {
+            return format!("a black node is missing on one side → {}", actions.join(", then "));
+        }
+        if actions.len() == 1 && recolours.len() == 1 {
+            return format!("the root has to be black → {}", actions[0]);
+        }
+        format!("uncle is red → recolour parent and uncle, the grandparent turns red ({})", recolours.join(", "))
+    }
+
+    // the sentence for the step that just finished, the next one starts from here
+    fn finish_step(&mut self) -> String {
+        self.step += 1;
+        let sentence = self.describe();
+        self.pending.clear();
+        sentence
+    }
+}
+
+impl<T: Clone> Explainer<T> {
+    // remember an event for the sentence of the current step
+    fn record(&mut self, event: &TreeEvent<T>) {
+        // every insert or delete starts afresh
+        if let TreeEvent::NodeCreated { .. } | TreeEvent::NodeRemoved { .. } = event {
+            self.deleting = matches!(event, TreeEvent::NodeRemoved { .. });
+            self.step = 0;
+            self.pending.clear();
+        }
+        self.pending.push(event.clone());
+    }
+}
+
+impl<T: Clone + Display> TreeObserver<T> for Explainer<T> {
+    fn on_event(&mut self, event: &TreeEvent<T>) {
+        if !self.enabled.get() {
+            return;
+        }
+        match event {
+            TreeEvent::Step { picture } => {
+                let sentence = self.finish_step();
+                println!("---");
+                println!("step {}: {}", self.step, sentence);
+                print!("{}", picture);
+                if self.pauses {
+                    println!("press enter to continue");
+                    // a failed read only means there is nobody to wait for
+                    let _ = io::stdin().read_line(&mut String::new());
+                }
+            }
+            _ => self.record(event),
+        }
+    }
+
+    fn wants_steps(&self) -> bool {
+        self.enabled.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    // the explainer of a tree, keeping its sentences instead of printing them
+    struct Sentences {
+        explainer: Explainer<u32>,
+        said: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl TreeObserver<u32> for Sentences {
+        fn on_event(&mut self, event: &TreeEvent<u32>) {
+            match event {
+                TreeEvent::Step { .. } => {
+                    let sentence = self.explainer.finish_step();
+                    self.said.borrow_mut().push(format!("step {}: {}", self.explainer.step, sentence));
+                }
+                _ => self.explainer.record(event),
+            }
+        }
+
+        fn wants_steps(&self) -> bool {
+            true
+        }
+    }
+
+    fn sentences(explainer: Explainer<u32>) -> (Box<Sentences>, Rc<RefCell<Vec<String>>>) {
+        let said = Rc::new(RefCell::new(Vec::new()));
+        (Box::new(Sentences { explainer, said: said.clone() }), said)
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn red_black_recolours_rotations_and_double_black() {
+        use crate::rb::RedBlackTree;
+
+        let mut tree = RedBlackTree::new();
+        let (observer, said) = sentences(Explainer::for_rb(Rc::new(Cell::new(true)), false));
+        tree.add_observer(observer);
+        tree.insert(10).unwrap();
+        assert_eq!(said.take(), ["step 1: inserted 10 as a red leaf", "step 2: the root has to be black → recolour 10 black"]);
+        tree.insert(20).unwrap();
+        assert_eq!(said.take(), ["step 1: inserted 20 as a red leaf"]);
+        tree.insert(30).unwrap();
+        assert_eq!(said.take(), [
+            "step 1: inserted 30 as a red leaf",
+            "step 2: red child under a red parent with a black uncle, RR case at 10 → rotate left at 10 (20 moves up), then recolour 20 black, 10 red",
+        ]);
+        tree.insert(40).unwrap();
+        assert_eq!(said.take(), [
+            "step 1: inserted 40 as a red leaf",
+            "step 2: uncle is red → recolour parent and uncle, the grandparent turns red (30 black, 10 black, 20 red)",
+            "step 3: the root has to be black → recolour 20 black",
+        ]);
+
+        // 10 is a black leaf, its sibling 30 is black with a red child on the far side
+        tree.remove(10).unwrap();
+        assert_eq!(said.take(), ["step 1: removed 10", "step 2: a black node is missing on one side → rotate left at 20 (30 moves up), then recolour 40 black"]);
+        // now the sibling has no red child, so it turns red and the root takes the missing black
+        tree.remove(40).unwrap();
+        assert_eq!(said.take(), ["step 1: removed 40", "step 2: a black node is missing on one side → recolour 20 red"]);
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn avl_single_and_double_rotations() {
+        use crate::avl::AVL_Tree;
+
+        let mut tree = AVL_Tree::new();
+        let (observer, said) = sentences(Explainer::for_avl(Rc::new(Cell::new(true)), false));
+        tree.add_observer(observer);
+        for key in [10, 20] {
+            tree.insert(key).unwrap();
+        }
+        said.take();
+        tree.insert(30).unwrap();
+        assert_eq!(said.take(), ["step 1: inserted 30 as a leaf", "step 2: balance factor −2 at 10, right child does not lean left → RR single left rotation"]);
+        tree.insert(5).unwrap();
+        tree.insert(1).unwrap();
+        assert_eq!(said.take(), [
+            "step 1: inserted 5 as a leaf",
+            "step 1: inserted 1 as a leaf",
+            "step 2: balance factor +2 at 10, left child does not lean right → LL single right rotation",
+        ]);
+        tree.insert(8).unwrap();
+        assert_eq!(said.take(), ["step 1: inserted 8 as a leaf", "step 2: balance factor +2 at 20, left child leans right → LR double rotation"]);
+        tree.insert(7).unwrap();
+        said.take();
+        tree.remove(20).unwrap();
+        assert_eq!(said.take(), ["step 1: removed 20", "step 2: balance factor +2 at 10, left child leans right → LR double rotation"]);
+    }
+}
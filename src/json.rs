@@ -0,0 +1,648 @@
+// a small json reader/writer and the tree shape documents built on it
+//
+// shape documents look like
+//   {"tree": "rb", "root": {"key": 5, "colour": "black", "left": null, "right": {...}}}
+//   {"tree": "avl", "root": {"key": 5, "height": 2, "left": null, "right": {...}}}
+// loading one rebuilds the exact shape and then validates it. string keys are json
+// strings, float keys numbers, or "inf" and "-inf" which json has no number for
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use crate::keys::{FloatKey, FoldedKey, StringKey};
+use crate::traversal::BinaryNode;
+#[cfg(feature = "avl")]
+use crate::avl::{AVL_Tree, AVL_Tree_Node};
+#[cfg(feature = "rb")]
+use crate::rb::{RBTreeNode, RedBlackTree};
+use crate::{RBTreeNodeColour, TreeError};
+
+// valid red-black and AVL trees stay far below this even with billions of keys,
+// anything deeper is rejected instead of recursing through it
+const MAX_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String), // kept as written so wide integers survive the round trip
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn get(&self, field: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(name, _)| name == field).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    // an object with the fields in the order given
+    pub fn object(fields: Vec<(&str, JsonValue)>) -> JsonValue {
+        JsonValue::Object(fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+    }
+
+    pub fn number(value: impl fmt::Display) -> JsonValue {
+        JsonValue::Number(value.to_string())
+    }
+}
+
+// quote a string for json output
+pub fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{}", value),
+            JsonValue::Number(number) => write!(f, "{}", number),
+            JsonValue::String(text) => write!(f, "{}", escape_json(text)),
+            JsonValue::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", escape_json(name), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// recursive descent parser over the raw bytes
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> TreeError {
+        TreeError::Parse(format!("{} at byte {}", message, self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.text.len() && self.text.as_bytes()[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), TreeError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, TreeError> {
+        if !self.text[self.pos..].starts_with(word) {
+            return Err(self.error("unexpected token"));
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JsonValue, TreeError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("document is nested too deeply"));
+        }
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(fields));
+                }
+                loop {
+                    let name = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((name, self.value(depth + 1)?));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Object(fields));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Array(items));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'"') => Ok(JsonValue::String(self.string()?)),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of document")),
+        }
+    }
+
+    // the digits from pos on, how many there were
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.text.as_bytes().get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    // -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)? as in RFC 8259, kept as text
+    fn number(&mut self) -> Result<JsonValue, TreeError> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        if bytes.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        match bytes.get(self.pos) {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => {
+                self.digits();
+            }
+            _ => return Err(self.error("expected a digit")),
+        }
+        if bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            if self.digits() == 0 {
+                return Err(self.error("expected a digit after the decimal point"));
+            }
+        }
+        if matches!(bytes.get(self.pos), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(bytes.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+        }
+        Ok(JsonValue::Number(self.text[start..self.pos].to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, TreeError> {
+        self.expect(b'"')?;
+        let mut text = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += offset + 1;
+                    return Ok(text);
+                }
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => {
+                            let unit = hex_unit(&mut chars).ok_or_else(|| self.error("bad unicode escape"))?;
+                            // characters outside the basic plane come as a high and a low surrogate
+                            let code = match unit {
+                                0xD800..=0xDBFF => {
+                                    let low = match (chars.next(), chars.next()) {
+                                        (Some((_, '\\')), Some((_, 'u'))) => hex_unit(&mut chars).filter(|low| (0xDC00..=0xDFFF).contains(low)),
+                                        _ => None,
+                                    };
+                                    let low = low.ok_or_else(|| self.error("unpaired surrogate in unicode escape"))?;
+                                    0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                                }
+                                0xDC00..=0xDFFF => return Err(self.error("unpaired surrogate in unicode escape")),
+                                unit => unit,
+                            };
+                            char::from_u32(code).ok_or_else(|| self.error("bad unicode escape"))?
+                        }
+                        _ => return Err(self.error("bad escape")),
+                    };
+                    text.push(escaped);
+                }
+                '\u{0}'..='\u{1f}' => return Err(self.error("control character in string")),
+                c => text.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+// the four hex digits of a \u escape
+fn hex_unit(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let mut unit = 0;
+    for _ in 0..4 {
+        unit = unit * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    Some(unit)
+}
+
+pub fn parse_json(text: &str) -> Result<JsonValue, TreeError> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.value(0)?;
+    if parser.peek().is_some() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+// keys that can be written to and read back from a shape document
+pub trait JsonKey: Sized {
+    fn to_json(&self) -> JsonValue;
+    fn from_json(value: &JsonValue) -> Option<Self>;
+}
+
+macro_rules! json_integer_key {
+    ($($t:ty),*) => {$(
+        impl JsonKey for $t {
+            fn to_json(&self) -> JsonValue {
+                return JsonValue::Number(self.to_string());
+            }
+            fn from_json(value: &JsonValue) -> Option<Self> {
+                match value {
+                    JsonValue::Number(number) => number.parse().ok(),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+json_integer_key!(i32, i64, u32, u64, i128);
+
+impl JsonKey for StringKey {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.0.clone())
+    }
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        value.as_str().map(|text| StringKey(text.to_string()))
+    }
+}
+
+impl JsonKey for FoldedKey {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.as_str().to_string())
+    }
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        value.as_str().map(FoldedKey::new)
+    }
+}
+
+impl JsonKey for FloatKey {
+    fn to_json(&self) -> JsonValue {
+        if self.value().is_infinite() {
+            return JsonValue::String(self.to_string());
+        }
+        JsonValue::Number(self.to_string())
+    }
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::Number(number) => number.parse().ok(),
+            JsonValue::String(text) if text == "inf" || text == "-inf" => text.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+// write the nested shape without recursing, node_fields gives everything but left and right
+fn write_shape<N: BinaryNode>(kind: &str, root: &Option<Rc<RefCell<N>>>, node_fields: impl Fn(&N) -> String) -> String {
+    enum Step<N> {
+        Node(Option<Rc<RefCell<N>>>),
+        Text(&'static str),
+    }
+
+    let mut json = format!("{{\"tree\":{},\"root\":", escape_json(kind));
+    let mut steps = vec![Step::Node(root.clone())];
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Text(text) => json.push_str(text),
+            Step::Node(None) => json.push_str("null"),
+            Step::Node(Some(node)) => {
+                let borrowed_node = node.borrow();
+                json.push('{');
+                json.push_str(&node_fields(&borrowed_node));
+                // pushed in reverse so they come back out in order
+                steps.push(Step::Text("}"));
+                steps.push(Step::Node(borrowed_node.right()));
+                steps.push(Step::Text(",\"right\":"));
+                steps.push(Step::Node(borrowed_node.left()));
+                steps.push(Step::Text(",\"left\":"));
+            }
+        }
+    }
+    json.push('}');
+    json
+}
+
+// check the tree field and hand back the root node, None for an empty tree
+fn shape_root<'a>(document: &'a JsonValue, kind: &str) -> Result<Option<&'a JsonValue>, TreeError> {
+    match document.get("tree").and_then(JsonValue::as_str) {
+        Some(found) if found == kind => {}
+        Some(found) => return Err(TreeError::Parse(format!("expected tree type {}, found {}", kind, found))),
+        None => return Err(TreeError::Parse("missing tree field".to_string())),
+    }
+    if document.get("root").is_none() {
+        return Err(TreeError::Parse("missing root field".to_string()));
+    }
+    shape_child(document, "root")
+}
+
+fn shape_key<K: JsonKey>(node: &JsonValue) -> Result<K, TreeError> {
+    let key = node.get("key").ok_or_else(|| TreeError::Parse("node without a key".to_string()))?;
+    K::from_json(key).ok_or_else(|| TreeError::Parse(format!("invalid key {}", key)))
+}
+
+fn shape_child<'a>(node: &'a JsonValue, side: &str) -> Result<Option<&'a JsonValue>, TreeError> {
+    match node.get(side) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(child @ JsonValue::Object(_)) => Ok(Some(child)),
+        Some(other) => Err(TreeError::Parse(format!("{} child must be an object or null, found {}", side, other))),
+    }
+}
+
+#[cfg(feature = "rb")]
+impl<T: Clone + Ord + fmt::Display + JsonKey> RedBlackTree<T> {
+    // exact shape of the tree, with the colour of every node
+    pub fn to_json(&self) -> String {
+        write_shape("rb", &self.root, |node: &RBTreeNode<T>| {
+            let colour = match node.color {
+                RBTreeNodeColour::Red => "red",
+                RBTreeNodeColour::Black => "black",
+            };
+            format!("\"key\":{},\"colour\":\"{}\"", node.key.to_json(), colour)
+        })
+    }
+
+    // rebuild a tree from a shape document, fails if it breaks the red-black rules
+    pub fn from_json(text: &str) -> Result<RedBlackTree<T>, TreeError> {
+        fn build<T: JsonKey>(node: &JsonValue, parent: Weak<RefCell<RBTreeNode<T>>>, len: &mut usize) -> Result<Rc<RefCell<RBTreeNode<T>>>, TreeError> {
+            let color = match node.get("colour").and_then(JsonValue::as_str) {
+                Some("red") => RBTreeNodeColour::Red,
+                Some("black") => RBTreeNodeColour::Black,
+                _ => return Err(TreeError::Parse("colour must be \"red\" or \"black\"".to_string())),
+            };
+            let built = Rc::new(RefCell::new(RBTreeNode { key: shape_key(node)?, parent, color, left: None, right: None }));
+            *len += 1;
+            if let Some(left) = shape_child(node, "left")? {
+                built.borrow_mut().left = Some(build(left, Rc::downgrade(&built), len)?);
+            }
+            if let Some(right) = shape_child(node, "right")? {
+                built.borrow_mut().right = Some(build(right, Rc::downgrade(&built), len)?);
+            }
+            Ok(built)
+        }
+
+        let document = parse_json(text)?;
+        let mut tree = RedBlackTree::new();
+        if let Some(root) = shape_root(&document, "rb")? {
+            tree.root = Some(build(root, Weak::new(), &mut tree.len)?);
+        }
+        tree.validate()?;
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "avl")]
+impl<T: Clone + Ord + fmt::Display + JsonKey> AVL_Tree<T> {
+    // exact shape of the tree, with the stored height of every node
+    pub fn to_json(&self) -> String {
+        write_shape("avl", &self.root, |node: &AVL_Tree_Node<T>| {
+            format!("\"key\":{},\"height\":{}", node.value.to_json(), node.height)
+        })
+    }
+
+    // rebuild a tree from a shape document, fails if the heights or balance are off
+    pub fn from_json(text: &str) -> Result<AVL_Tree<T>, TreeError> {
+        fn build<T: JsonKey>(node: &JsonValue, len: &mut usize) -> Result<Rc<RefCell<AVL_Tree_Node<T>>>, TreeError> {
+            let height = match node.get("height") {
+                Some(JsonValue::Number(height)) => height.parse().map_err(|_| TreeError::Parse(format!("invalid height {}", height)))?,
+                _ => return Err(TreeError::Parse("node without a height".to_string())),
+            };
+            let built = AVL_Tree_Node::new(shape_key(node)?);
+            built.borrow_mut().height = height;
+            *len += 1;
+            if let Some(left) = shape_child(node, "left")? {
+                built.borrow_mut().left = Some(build(left, len)?);
+            }
+            if let Some(right) = shape_child(node, "right")? {
+                built.borrow_mut().right = Some(build(right, len)?);
+            }
+            Ok(built)
+        }
+
+        let document = parse_json(text)?;
+        let mut tree = AVL_Tree::new();
+        if let Some(root) = shape_root(&document, "avl")? {
+            tree.root = Some(build(root, &mut tree.len)?);
+        }
+        tree.validate()?;
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the message of a failed load, which says what was wrong with the document
+    fn error_of<T>(result: Result<T, TreeError>) -> String {
+        result.err().expect("the document should have been rejected").to_string()
+    }
+
+    #[test]
+    fn parser_reads_values_and_escapes() {
+        let value = parse_json(r#" {"a": [1, -2.5e3, true, null], "b": "x\"\\\/\né"} "#).unwrap();
+        assert_eq!(value.get("a"), Some(&JsonValue::Array(vec![JsonValue::number(1), JsonValue::number("-2.5e3"), JsonValue::Bool(true), JsonValue::Null])));
+        assert_eq!(value.get("b").and_then(JsonValue::as_str), Some("x\"\\/\né"));
+        let written = JsonValue::String("tab\there \u{1}".to_string()).to_string();
+        assert_eq!(written, r#""tab\there \u0001""#);
+        assert_eq!(parse_json(&written).unwrap().as_str(), Some("tab\there \u{1}"));
+    }
+
+    #[test]
+    fn parser_rejects_bad_escapes_and_broken_documents() {
+        assert!(error_of(parse_json(r#""\q""#)).contains("bad escape"));
+        assert!(error_of(parse_json(r#""\u12""#)).contains("bad unicode escape"));
+        assert!(error_of(parse_json(r#""\u12g4""#)).contains("bad unicode escape"));
+        assert!(error_of(parse_json(r#""open"#)).contains("unterminated string"));
+        assert!(error_of(parse_json("[1, 2")).contains("expected ',' or ']'"));
+        assert!(error_of(parse_json("{} {}")).contains("trailing characters"));
+        assert!(error_of(parse_json("nul")).contains("unexpected token"));
+    }
+
+    #[test]
+    fn numbers_follow_the_rfc_grammar() {
+        for number in ["0", "-0", "12", "-1.5", "1e10", "1E+2", "2.5e-3"] {
+            assert_eq!(parse_json(number).unwrap(), JsonValue::number(number), "{}", number);
+        }
+        for (text, error) in [("-", "expected a digit"), ("+1", "unexpected character"), ("--1", "expected a digit"), (".5", "unexpected character"), ("1.", "after the decimal point"), ("1e", "in the exponent"), ("1e+", "in the exponent")] {
+            assert!(error_of(parse_json(text)).contains(error), "{}", text);
+        }
+        // a number ends where the grammar does, whatever follows is not part of it
+        assert!(error_of(parse_json("1.2.3")).contains("trailing characters"));
+        assert!(error_of(parse_json("01")).contains("trailing characters"));
+        assert!(error_of(parse_json("[1.2.3]")).contains("expected ',' or ']'"));
+    }
+
+    #[test]
+    fn strings_refuse_control_characters_and_pair_surrogates() {
+        assert!(error_of(parse_json("\"a\u{1}b\"")).contains("control character in string"));
+        assert!(error_of(parse_json("\"two\nlines\"")).contains("control character in string"));
+        assert_eq!(parse_json(r#""\uD83D\uDE00 \u00e9""#).unwrap().as_str(), Some("\u{1F600} é"));
+        assert!(error_of(parse_json(r#""\ud800""#)).contains("unpaired surrogate"));
+        assert!(error_of(parse_json(r#""\ud800\n""#)).contains("unpaired surrogate"));
+        assert!(error_of(parse_json(r#""\ud800\u0041""#)).contains("unpaired surrogate"));
+        assert!(error_of(parse_json(r#""\ude00""#)).contains("unpaired surrogate"));
+    }
+
+    #[test]
+    fn parser_stops_at_the_depth_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse_json(&nested(MAX_DEPTH)).is_ok());
+        assert!(error_of(parse_json(&nested(MAX_DEPTH + 2))).contains("nested too deeply"));
+        // deep enough to overflow the stack if the limit were not checked
+        assert!(error_of(parse_json(&nested(1_000_000))).contains("nested too deeply"));
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn rb_shape_survives_the_round_trip() {
+        let mut tree = RedBlackTree::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 25, 5, 1] {
+            tree.insert(key).unwrap();
+        }
+        tree.remove(80).unwrap();
+        let json = tree.to_json();
+        let loaded = RedBlackTree::<i64>::from_json(&json).unwrap();
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(loaded.len(), tree.len());
+
+        let mut words = RedBlackTree::new();
+        for word in ["b", "a \"quoted\" word", "c\\d"] {
+            words.insert(StringKey(word.to_string())).unwrap();
+        }
+        assert_eq!(RedBlackTree::<StringKey>::from_json(&words.to_json()).unwrap().to_json(), words.to_json());
+        assert_eq!(RedBlackTree::<i64>::from_json(r#"{"tree":"rb","root":null}"#).unwrap().len(), 0);
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn rb_import_rejects_broken_trees() {
+        let load = |text: &str| error_of(RedBlackTree::<i64>::from_json(text));
+        let leaf = |key: i64, colour: &str| format!(r#"{{"key":{},"colour":"{}","left":null,"right":null}}"#, key, colour);
+        assert!(load(&format!(r#"{{"tree":"rb","root":{}}}"#, leaf(1, "red"))).contains("the root is red"));
+        let uneven = format!(r#"{{"tree":"rb","root":{{"key":2,"colour":"black","left":{},"right":null}}}}"#, leaf(1, "black"));
+        assert!(load(&uneven).contains("uneven black height"));
+        let red_red = format!(r#"{{"tree":"rb","root":{{"key":3,"colour":"black","left":{{"key":2,"colour":"red","left":{},"right":null}},"right":{}}}}}"#, leaf(1, "red"), leaf(4, "black"));
+        assert!(load(&red_red).contains("has a red child"));
+        let unordered = format!(r#"{{"tree":"rb","root":{{"key":2,"colour":"black","left":{},"right":null}}}}"#, leaf(3, "red"));
+        assert!(load(&unordered).contains("out of order"));
+        assert!(load(&format!(r#"{{"tree":"avl","root":{}}}"#, leaf(1, "black"))).contains("expected tree type rb"));
+        assert!(load(r#"{"tree":"rb","root":{"key":1,"colour":"blue"}}"#).contains("colour must be"));
+        assert!(load(r#"{"tree":"rb","root":{"key":"1","colour":"black"}}"#).contains("invalid key"));
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn avl_shape_survives_the_round_trip() {
+        let mut tree = AVL_Tree::new();
+        for key in 0..100 {
+            tree.insert(key * 7 % 101).unwrap();
+        }
+        let json = tree.to_json();
+        let loaded = AVL_Tree::<i64>::from_json(&json).unwrap();
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(loaded.len(), 100);
+
+        let mut floats = AVL_Tree::new();
+        for value in [1.5, f64::INFINITY, -0.25, f64::NEG_INFINITY] {
+            floats.insert(FloatKey::new(value).unwrap()).unwrap();
+        }
+        assert_eq!(AVL_Tree::<FloatKey>::from_json(&floats.to_json()).unwrap().to_json(), floats.to_json());
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn avl_import_rejects_broken_trees() {
+        let load = |text: &str| error_of(AVL_Tree::<i64>::from_json(text));
+        let leaf = |key: i64, height: i32| format!(r#"{{"key":{},"height":{},"left":null,"right":null}}"#, key, height);
+        let stale = format!(r#"{{"tree":"avl","root":{{"key":2,"height":1,"left":{},"right":null}}}}"#, leaf(1, 1));
+        assert!(load(&stale).contains("stale height"));
+        let leaning = format!(r#"{{"tree":"avl","root":{{"key":3,"height":3,"left":{{"key":2,"height":2,"left":{},"right":null}},"right":null}}}}"#, leaf(1, 1));
+        assert!(load(&leaning).contains("balancing factor"));
+        assert!(load(r#"{"tree":"avl","root":{"key":1}}"#).contains("node without a height"));
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn import_stops_at_the_depth_limit() {
+        let mut chain = "null".to_string();
+        for key in 0..MAX_DEPTH as i64 + 10 {
+            chain = format!(r#"{{"key":{},"height":1,"left":null,"right":{}}}"#, key, chain);
+        }
+        let text = format!(r#"{{"tree":"avl","root":{}}}"#, chain);
+        assert!(error_of(AVL_Tree::<i64>::from_json(&text)).contains("nested too deeply"));
+    }
+}
@@ -0,0 +1,177 @@
+// key types beyond the integers: strings in byte order, strings compared without case,
+// and floats in a total order
+//
+// strings are shown in double quotes so "10" can not be mistaken for the number 10, and
+// typed text may be quoted the same way. floats are shown with a decimal point, NaN is
+// refused when parsing so every float key has a place in the order.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+// a quoted word loses its quotes, anything else is taken as it is, but a quote at only one
+// end is a typing mistake rather than part of the word
+fn unquote(text: &str) -> Result<&str, UnbalancedQuote> {
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        return Ok(&text[1..text.len() - 1]);
+    }
+    if text.starts_with('"') || text.ends_with('"') {
+        return Err(UnbalancedQuote);
+    }
+    Ok(text)
+}
+
+#[derive(Debug)]
+pub struct UnbalancedQuote;
+
+// strings ordered by their bytes, which is the order of their code points
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StringKey(pub String);
+
+impl FromStr for StringKey {
+    type Err = UnbalancedQuote;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(StringKey(unquote(text)?.to_string()))
+    }
+}
+
+impl fmt::Display for StringKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+// strings compared in lower case, two spellings of the same word are the same key and the
+// tree keeps the one inserted first
+#[derive(Clone, Debug)]
+pub struct FoldedKey {
+    text: String,
+    folded: String,
+}
+
+impl FoldedKey {
+    pub fn new(text: &str) -> Self {
+        FoldedKey { text: text.to_string(), folded: text.to_lowercase() }
+    }
+
+    // the spelling the key was made with
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl PartialEq for FoldedKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.folded == other.folded
+    }
+}
+
+impl Eq for FoldedKey {}
+
+impl PartialOrd for FoldedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FoldedKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.folded.cmp(&other.folded)
+    }
+}
+
+impl FromStr for FoldedKey {
+    type Err = UnbalancedQuote;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(FoldedKey::new(unquote(text)?))
+    }
+}
+
+impl fmt::Display for FoldedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.text)
+    }
+}
+
+// a float that is never NaN, ordered like f64::total_cmp with -0 taken as 0
+#[derive(Clone, Copy, Debug)]
+pub struct FloatKey(f64);
+
+impl FloatKey {
+    pub fn new(value: f64) -> Option<Self> {
+        if value.is_nan() {
+            return None;
+        }
+        // -0 and 0 compare equal as numbers, so they are one key
+        Some(FloatKey(if value == 0.0 { 0.0 } else { value }))
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl PartialEq for FloatKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FloatKey {}
+
+impl PartialOrd for FloatKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FloatKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotAFloat;
+
+impl FromStr for FloatKey {
+    type Err = NotAFloat;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        text.parse::<f64>().ok().and_then(FloatKey::new).ok_or(NotAFloat)
+    }
+}
+
+// always with a decimal point or an exponent, so 1 shows as 1.0
+impl fmt::Display for FloatKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_must_come_in_pairs() {
+        assert_eq!("\"new york\"".parse::<StringKey>().unwrap(), StringKey("new york".to_string()));
+        assert_eq!("\"\"".parse::<StringKey>().unwrap(), StringKey(String::new()));
+        assert_eq!("a\"b".parse::<StringKey>().unwrap(), StringKey("a\"b".to_string()));
+        for lone in ["\"", "\"abc", "abc\""] {
+            assert!(lone.parse::<StringKey>().is_err(), "{}", lone);
+            assert!(lone.parse::<FoldedKey>().is_err(), "{}", lone);
+        }
+    }
+
+    #[test]
+    fn floats_refuse_nan_and_keep_one_zero() {
+        for nan in ["NaN", "nan", "-nan"] {
+            assert!(nan.parse::<FloatKey>().is_err(), "{}", nan);
+        }
+        assert!(FloatKey::new(f64::NAN).is_none());
+        let zero: FloatKey = "-0".parse().unwrap();
+        assert_eq!(zero, FloatKey::new(0.0).unwrap());
+        assert_eq!(zero.to_string(), "0.0");
+        assert!("-inf".parse::<FloatKey>().unwrap() < FloatKey::new(f64::MIN).unwrap());
+    }
+}
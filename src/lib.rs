@@ -0,0 +1,115 @@
+// sources:
+// https://www.geeksforgeeks.org/deletion-in-red-black-tree/?ref=lbp
+// https://www.reddit.com/r/rust/comments/102yhtv/red_black_tree_in_rust/
+// https://subscription.packtpub.com/book/application-development/9781838828103/15/ch15lvl1sec119/red-black-tree
+// https://codereview.stackexchange.com/questions/190041/red-black-tree-in-rust
+// https://cglab.ca/~abeinges/blah/rust-btree-case/
+
+
+// imports/allows
+// slimmer feature sets leave some of the shared helpers unused
+#![cfg_attr(not(all(feature = "rb", feature = "avl", feature = "cli")), allow(dead_code, unused_imports))]
+use std::fmt;
+use std::io;
+
+// the trees are behind the `rb` and `avl` features, the interactive driver behind `cli`
+#[cfg(feature = "cli")]
+mod args;
+#[cfg(feature = "avl")]
+mod avl;
+#[cfg(feature = "cli")]
+mod cli;
+#[cfg(feature = "cli")]
+mod command;
+#[cfg(feature = "rb")]
+mod cursor;
+mod dot;
+#[cfg(feature = "cli")]
+mod dyntree;
+#[cfg(feature = "cli")]
+mod explain;
+mod json;
+mod keys;
+mod observer;
+#[cfg(feature = "rb")]
+mod rb;
+#[cfg(feature = "cli")]
+mod savefile;
+#[cfg(feature = "cli")]
+mod script;
+mod stats;
+mod svg;
+mod traversal;
+#[cfg(feature = "cli")]
+mod workload;
+
+#[cfg(feature = "cli")]
+pub use args::{parse_args, run_args, run_batch, BatchOptions, CliCommand, Report, USAGE};
+#[cfg(feature = "avl")]
+pub use avl::{AVL_Tree, AVL_Tree_Node};
+#[cfg(feature = "cli")]
+pub use cli::{run, run_repl};
+#[cfg(feature = "rb")]
+pub use cursor::{Cursor, CursorMut};
+#[cfg(feature = "cli")]
+pub use dyntree::{KeyType, TreeKind};
+pub use json::{parse_json, JsonKey, JsonValue};
+pub use keys::{FloatKey, FoldedKey, NotAFloat, StringKey, UnbalancedQuote};
+pub use observer::{BalanceCase, TreeEvent, TreeObserver, TreeObservers};
+#[cfg(feature = "rb")]
+pub use rb::{RBTree, RBTreeFunctions, RBTreeNode, RedBlackTree, RedBlackTree_Op};
+pub use stats::TreeStats;
+pub use traversal::{print_levels, BinaryNode, InOrderIter, LevelOrderIter, PostOrderIter, PreOrderIter, RangeIter};
+#[cfg(feature = "cli")]
+pub use workload::{Order, Workload};
+
+// color enum
+#[derive(Clone, Debug, PartialEq)]
+pub enum RBTreeNodeColour {
+    Red,
+    Black,
+}
+
+// errors returned by the trees and the command loops instead of panicking
+#[derive(Debug)]
+pub enum TreeError {
+    MissingChild(&'static str), // a rotation needs a child on this side
+    InvariantBroken(String),    // the tree no longer satisfies its balancing rules
+    DuplicateKey(String),       // the key is already in a tree that is in strict mode
+    Parse(String),              // a document could not be read back into a tree
+    InvalidKey(String),         // typed text is not a key this tree can hold
+    KeyOutOfRange(String, String), // a number too large or too small for the key type, and the range it has
+    Io(io::Error),              // reading commands failed
+    Command(String),            // a command failed while running in strict mode
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TreeError::MissingChild(side) => write!(f, "missing {} child", side),
+            TreeError::InvariantBroken(reason) => write!(f, "tree invariant broken: {}", reason),
+            TreeError::DuplicateKey(key) => write!(f, "key {} already exists", key),
+            TreeError::Parse(reason) => write!(f, "could not parse document: {}", reason),
+            TreeError::InvalidKey(key) => write!(f, "'{}' is not a valid key", key),
+            TreeError::KeyOutOfRange(key, range) => write!(f, "{} is out of range, keys are {}", key, range),
+            TreeError::Io(err) => write!(f, "i/o error: {}", err),
+            TreeError::Command(reason) => write!(f, "command failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TreeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TreeError {
+    fn from(err: io::Error) -> Self {
+        TreeError::Io(err)
+    }
+}
+
@@ -0,0 +1,230 @@
+// structural events raised by the trees while they insert, delete and rebalance
+
+use crate::stats::TreeStats;
+use crate::RBTreeNodeColour;
+
+// which way a subtree was out of balance, named after the path from the unbalanced
+// node down to the newly heavy grandchild; LeftLeft and RightRight need one rotation,
+// LeftRight and RightLeft need two
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BalanceCase {
+    LeftLeft,
+    LeftRight,
+    RightRight,
+    RightLeft,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeEvent<T> {
+    NodeCreated { key: T },
+    NodeRemoved { key: T },
+    // node moves down, pivot (its old right child) takes its place
+    RotateLeft { node: T, pivot: T },
+    // node moves down, pivot (its old left child) takes its place
+    RotateRight { node: T, pivot: T },
+    // raised at the node being fixed, before its rotations
+    Rebalance { case: BalanceCase, key: T },
+    Recolour { key: T, colour: RBTreeNodeColour },
+    HeightUpdated { key: T, height: i32 },
+    // one rebalancing step is finished and the tree is whole again; the picture is the
+    // tree as print_tree or print_avl_tree would show it
+    Step { picture: String },
+}
+
+pub trait TreeObserver<T> {
+    fn on_event(&mut self, event: &TreeEvent<T>);
+
+    // drawing the tree after every step is expensive, so it only happens on request
+    fn wants_steps(&self) -> bool {
+        false
+    }
+}
+
+// plain closures can subscribe too
+impl<T, F: FnMut(&TreeEvent<T>)> TreeObserver<T> for F {
+    fn on_event(&mut self, event: &TreeEvent<T>) {
+        self(event);
+    }
+}
+
+// the subscribers of one tree, along with the counters of the operation in progress
+pub struct TreeObservers<T> {
+    observers: Vec<Box<dyn TreeObserver<T>>>,
+    pub(crate) stats: TreeStats,
+}
+
+impl<T> Default for TreeObservers<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TreeObservers<T> {
+    pub fn new() -> Self {
+        Self { observers: Vec::new(), stats: TreeStats::default() }
+    }
+
+    // hand over the counters of the finished operation and start again from zero
+    pub fn take_stats(&mut self) -> TreeStats {
+        std::mem::take(&mut self.stats)
+    }
+
+    pub fn add(&mut self, observer: Box<dyn TreeObserver<T>>) {
+        self.observers.push(observer);
+    }
+
+    pub fn clear(&mut self) {
+        self.observers.clear();
+    }
+
+    // events are only built when somebody is listening, so keys are not cloned for nothing
+    pub fn emit(&mut self, event: impl FnOnce() -> TreeEvent<T>) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let event = event();
+        for observer in self.observers.iter_mut() {
+            observer.on_event(&event);
+        }
+    }
+
+    // mark the end of a rebalancing step, the picture is only drawn if somebody wants it
+    pub fn step(&mut self, picture: impl FnOnce() -> String) {
+        if !self.observers.iter().any(|observer| observer.wants_steps()) {
+            return;
+        }
+        self.emit(|| TreeEvent::Step { picture: picture() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use BalanceCase::*;
+    use TreeEvent::*;
+
+    type Events = Rc<RefCell<Vec<TreeEvent<i32>>>>;
+
+    // a closure observer that keeps every event, and the handle to take them out with
+    fn recorder() -> (Box<dyn TreeObserver<i32>>, Events) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&events);
+        (Box::new(move |event: &TreeEvent<i32>| seen.borrow_mut().push(event.clone())), events)
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn red_black_events_come_in_order() {
+        let (observer, events) = recorder();
+        let mut tree = crate::RedBlackTree::new();
+        tree.add_observer(observer);
+        tree.insert(1).unwrap();
+        assert_eq!(events.take(), [NodeCreated { key: 1 }, Recolour { key: 1, colour: RBTreeNodeColour::Black }]);
+        tree.insert(2).unwrap();
+        assert_eq!(events.take(), [NodeCreated { key: 2 }]);
+        tree.insert(3).unwrap();
+        assert_eq!(
+            events.take(),
+            [
+                NodeCreated { key: 3 },
+                Rebalance { case: RightRight, key: 1 },
+                RotateLeft { node: 1, pivot: 2 },
+                Recolour { key: 2, colour: RBTreeNodeColour::Black },
+                Recolour { key: 1, colour: RBTreeNodeColour::Red },
+            ]
+        );
+
+        // nothing happens, so nothing is said
+        tree.insert(2).unwrap();
+        tree.remove(7).unwrap();
+        assert_eq!(events.take(), []);
+
+        tree.remove(1).unwrap();
+        assert_eq!(events.take(), [NodeRemoved { key: 1 }]);
+        tree.remove(2).unwrap();
+        assert_eq!(events.take(), [NodeRemoved { key: 2 }, Recolour { key: 3, colour: RBTreeNodeColour::Black }]);
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn avl_events_come_in_order() {
+        let (observer, events) = recorder();
+        let mut tree = crate::AVL_Tree::new();
+        tree.add_observer(observer);
+        tree.insert(3).unwrap();
+        tree.insert(1).unwrap();
+        assert_eq!(events.take(), [NodeCreated { key: 3 }, NodeCreated { key: 1 }, HeightUpdated { key: 3, height: 2 }]);
+        tree.insert(2).unwrap();
+        assert_eq!(
+            events.take(),
+            [
+                NodeCreated { key: 2 },
+                HeightUpdated { key: 1, height: 2 },
+                HeightUpdated { key: 3, height: 3 },
+                Rebalance { case: LeftRight, key: 3 },
+                RotateLeft { node: 1, pivot: 2 },
+                HeightUpdated { key: 1, height: 1 },
+                HeightUpdated { key: 2, height: 2 },
+                RotateRight { node: 3, pivot: 2 },
+                HeightUpdated { key: 3, height: 1 },
+            ]
+        );
+        tree.insert(4).unwrap();
+        tree.insert(5).unwrap();
+        assert_eq!(
+            events.take(),
+            [
+                NodeCreated { key: 4 },
+                HeightUpdated { key: 3, height: 2 },
+                HeightUpdated { key: 2, height: 3 },
+                NodeCreated { key: 5 },
+                HeightUpdated { key: 4, height: 2 },
+                HeightUpdated { key: 3, height: 3 },
+                Rebalance { case: RightRight, key: 3 },
+                RotateLeft { node: 3, pivot: 4 },
+                HeightUpdated { key: 3, height: 1 },
+            ]
+        );
+
+        tree.remove(9).unwrap();
+        assert_eq!(events.take(), []);
+        tree.remove(1).unwrap();
+        assert_eq!(
+            events.take(),
+            [NodeRemoved { key: 1 }, Rebalance { case: RightRight, key: 2 }, RotateLeft { node: 2, pivot: 4 }, HeightUpdated { key: 2, height: 2 }, HeightUpdated { key: 4, height: 3 }]
+        );
+    }
+
+    // steps are only drawn for an observer that asks for them
+    #[cfg(feature = "rb")]
+    #[test]
+    fn steps_go_only_to_those_who_want_them() {
+        struct Steps(Rc<RefCell<Vec<String>>>);
+        impl TreeObserver<i32> for Steps {
+            fn on_event(&mut self, event: &TreeEvent<i32>) {
+                if let Step { picture } = event {
+                    self.0.borrow_mut().push(picture.clone());
+                }
+            }
+            fn wants_steps(&self) -> bool {
+                true
+            }
+        }
+
+        let (observer, events) = recorder();
+        let mut tree = crate::RedBlackTree::new();
+        tree.add_observer(observer);
+        tree.insert(1).unwrap();
+        assert!(!events.take().iter().any(|event| matches!(event, Step { .. })));
+
+        let pictures = Rc::new(RefCell::new(Vec::new()));
+        tree.add_observer(Box::new(Steps(Rc::clone(&pictures))));
+        tree.insert(2).unwrap();
+        tree.insert(3).unwrap();
+        assert!(!pictures.borrow().is_empty());
+        // once drawn, every observer hears of the step
+        assert_eq!(events.take().iter().filter(|event| matches!(event, Step { .. })).count(), pictures.borrow().len());
+    }
+}
@@ -0,0 +1,807 @@
+// red-black tree: the node type, the low level functions on the root option and the
+// RedBlackTree wrapper around them
+
+use std::cell::RefCell;
+use std::cmp::{max, Ordering};
+use std::fmt::Display;
+use std::rc::{Rc, Weak};
+
+use crate::observer::{BalanceCase, TreeEvent, TreeObserver, TreeObservers};
+use crate::stats::TreeStats;
+use crate::traversal::{InOrderIter, LevelOrderIter, PostOrderIter, PreOrderIter, RangeIter};
+use crate::{RBTreeNodeColour, TreeError};
+
+// types
+pub type RBTree<T> = Rc<RefCell<RBTreeNode<T>>>;
+#[allow(non_camel_case_types)] // the name the tree code has always used
+pub type RedBlackTree_Op<T> = Option<RBTree<T>>;
+
+// rbtree struct
+pub struct RBTreeNode<T> {
+    pub(crate) key: T,
+    pub(crate) parent: Weak<RefCell<RBTreeNode<T>>>,
+    pub(crate) color: RBTreeNodeColour,
+    pub(crate) left: Option<Rc<RefCell<RBTreeNode<T>>>>,
+    pub(crate) right: Option<Rc<RefCell<RBTreeNode<T>>>>,
+}
+
+impl<T> RBTreeNode<T> {
+    // a node with no parent and no children yet
+    pub(crate) fn new(key: T, color: RBTreeNodeColour) -> RBTree<T> {
+        Rc::new(RefCell::new(RBTreeNode { key, parent: Weak::new(), color, left: None, right: None }))
+    }
+}
+
+// all functions of rbtree
+pub trait RBTreeFunctions<T> {
+    fn new(value: T) -> Self;
+    // fails with DuplicateKey, leaving the tree as it was, if the value is already there
+    fn insert_node(&mut self, value: T, observers: &mut TreeObservers<T>) -> Result<(), TreeError>;
+    fn rotate_left(node: &RBTree<T>) -> Result<RBTree<T>, TreeError>;
+    fn rotate_right(node: &RBTree<T>) -> Result<RBTree<T>, TreeError>;
+    fn is_node_exists(&self, value: &T) -> bool;
+    fn find_node(&self, value: &T) -> RedBlackTree_Op<T>;
+    fn delete_node(&mut self, node: &RBTree<T>, observers: &mut TreeObservers<T>) -> Result<RedBlackTree_Op<T>, TreeError>;
+
+    fn count_leaves(&self) -> u32;
+    fn get_tree_height(&self) -> u32;
+    fn print_traversal(&self);
+    fn is_tree_empty(&self) -> bool;
+}
+
+// the children of a node are owned through `Rc`, so tearing down a long chain
+// would recurse once per level; unlink the subtrees onto a stack instead
+impl<T> Drop for RBTreeNode<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Rc<RefCell<RBTreeNode<T>>>> = Vec::new();
+        stack.extend(self.left.take());
+        stack.extend(self.right.take());
+        while let Some(node) = stack.pop() {
+            if let Ok(cell) = Rc::try_unwrap(node) {
+                let mut node = cell.into_inner();
+                stack.extend(node.left.take());
+                stack.extend(node.right.take());
+            }
+        }
+    }
+}
+
+// check whether `child` hangs off the left of `node`
+pub(crate) fn is_left_child<T>(node: &RBTree<T>, child: &RBTree<T>) -> bool {
+    return node.borrow().left.as_ref().is_some_and(|left| Rc::ptr_eq(left, child));
+}
+
+// empty children count as black
+pub(crate) fn is_black<T>(node: &RedBlackTree_Op<T>) -> bool {
+    node.as_ref().is_none_or(|node| node.borrow().color == RBTreeNodeColour::Black)
+}
+
+// leftmost node below (and including) node
+pub(crate) fn minimum_node<T>(node: &RBTree<T>) -> RBTree<T> {
+    let mut current = node.clone();
+    loop {
+        let left = current.borrow().left.clone();
+        match left {
+            Some(left) => current = left,
+            None => return current,
+        }
+    }
+}
+
+// rightmost node below (and including) node
+pub(crate) fn maximum_node<T>(node: &RBTree<T>) -> RBTree<T> {
+    let mut current = node.clone();
+    loop {
+        let right = current.borrow().right.clone();
+        match right {
+            Some(right) => current = right,
+            None => return current,
+        }
+    }
+}
+
+// next node in key order, found through the parent pointers
+pub(crate) fn successor_node<T>(node: &RBTree<T>) -> RedBlackTree_Op<T> {
+    if let Some(right) = node.borrow().right.clone() {
+        return Some(minimum_node(&right));
+    }
+    let mut current = node.clone();
+    loop {
+        let parent = current.borrow().parent.upgrade()?;
+        if is_left_child(&parent, &current) {
+            return Some(parent);
+        }
+        current = parent;
+    }
+}
+
+// previous node in key order, found through the parent pointers
+pub(crate) fn predecessor_node<T>(node: &RBTree<T>) -> RedBlackTree_Op<T> {
+    if let Some(left) = node.borrow().left.clone() {
+        return Some(maximum_node(&left));
+    }
+    let mut current = node.clone();
+    loop {
+        let parent = current.borrow().parent.upgrade()?;
+        if !is_left_child(&parent, &current) {
+            return Some(parent);
+        }
+        current = parent;
+    }
+}
+
+// plain binary search, counting the nodes it compares against
+pub(crate) fn search_rb<T: Ord>(root: &RedBlackTree_Op<T>, data: &T, comparisons: &mut u64) -> RedBlackTree_Op<T> {
+    let mut current = root.clone();
+    while let Some(node) = current {
+        *comparisons += 1;
+        if node.borrow().key == *data {
+            return Some(node);
+        }
+        current = if node.borrow().key < *data { node.borrow().right.clone() } else { node.borrow().left.clone() };
+    }
+    None
+}
+
+// rotate and take over the root if the pivot ended up on top
+pub(crate) fn rotate_rb_at<T: Clone + Ord + Display>(tree_root: &mut RedBlackTree_Op<T>, node: &RBTree<T>, left: bool, observers: &mut TreeObservers<T>) -> Result<RBTree<T>, TreeError> {
+    let pivot = if left { RedBlackTree_Op::<T>::rotate_left(node)? } else { RedBlackTree_Op::<T>::rotate_right(node)? };
+    if pivot.borrow().parent.upgrade().is_none() {
+        *tree_root = Some(pivot.clone());
+    }
+    let (node_key, pivot_key) = (node.borrow().key.clone(), pivot.borrow().key.clone());
+    observers.emit(|| if left {
+        TreeEvent::RotateLeft { node: node_key.clone(), pivot: pivot_key.clone() }
+    } else {
+        TreeEvent::RotateRight { node: node_key.clone(), pivot: pivot_key.clone() }
+    });
+    Ok(pivot)
+}
+
+// change the colour of a node, only reported when it actually changes
+pub(crate) fn recolour_rb<T: Clone>(node: &RBTree<T>, colour: RBTreeNodeColour, observers: &mut TreeObservers<T>) -> bool {
+    if node.borrow().color == colour {
+        return false;
+    }
+    node.borrow_mut().color = colour.clone();
+    observers.stats.colour_flips += 1;
+    let key = node.borrow().key.clone();
+    observers.emit(|| TreeEvent::Recolour { key: key.clone(), colour: colour.clone() });
+    true
+}
+
+// rbtree implementation
+impl<T: Clone + Ord + Display> RBTreeFunctions<T> for RedBlackTree_Op<T> {
+
+    // create rbtree
+    fn new(value: T) -> RedBlackTree_Op<T> {
+        Some(RBTreeNode::new(value, RBTreeNodeColour::Black))
+    }
+
+    // rotate rbtree left
+    // the pivot takes the place of current_node under its old parent
+    fn rotate_left(current_node: &RBTree<T>) -> Result<RBTree<T>, TreeError> {
+
+        let prev = current_node.as_ref().borrow().right.clone().ok_or(TreeError::MissingChild("right"))?;
+        let lat = &prev.borrow().left.clone();
+        let grandparent = current_node.borrow().parent.upgrade();
+        prev.borrow_mut().left = Some(current_node.clone());
+        prev.borrow_mut().parent = current_node.borrow().parent.clone();
+
+        current_node.borrow_mut().right = lat.clone();
+        current_node.borrow_mut().parent = Rc::downgrade(&prev);
+
+        if !lat.is_none() {
+            lat.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(current_node);
+        }
+        if let Some(grandparent) = grandparent {
+            if is_left_child(&grandparent, current_node) {
+                grandparent.borrow_mut().left = Some(prev.clone());
+            } else {
+                grandparent.borrow_mut().right = Some(prev.clone());
+            }
+        }
+        Ok(prev)
+    }
+
+    // rotate rbtree right
+    // the pivot takes the place of current_node under its old parent
+    fn rotate_right(current_node: &RBTree<T>) -> Result<RBTree<T>, TreeError> {
+
+        let prev = current_node.as_ref().borrow().left.clone().ok_or(TreeError::MissingChild("left"))?;
+        let lat = &prev.borrow().right.clone();
+        let grandparent = current_node.borrow().parent.upgrade();
+        prev.borrow_mut().right = Some(current_node.clone());
+        prev.borrow_mut().parent = current_node.borrow().parent.clone();
+
+        current_node.borrow_mut().left = lat.clone();
+        current_node.borrow_mut().parent = Rc::downgrade(&prev);
+
+        if !lat.is_none() {
+            lat.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(current_node);
+        }
+        if let Some(grandparent) = grandparent {
+            if is_left_child(&grandparent, current_node) {
+                grandparent.borrow_mut().left = Some(prev.clone());
+            } else {
+                grandparent.borrow_mut().right = Some(prev.clone());
+            }
+        }
+        Ok(prev)
+    }
+
+    // rbtree insert
+    fn insert_node(&mut self, value: T, observers: &mut TreeObservers<T>) -> Result<(), TreeError> {
+
+        // balancing logic, walks back up from the inserted node through the parent pointers
+        fn insert_cl_logic<T: Clone + Ord + Display>(tree_root: &mut RedBlackTree_Op<T>, inserted: RBTree<T>, observers: &mut TreeObservers<T>) -> Result<(), TreeError> {
+
+            let mut subroot = inserted;
+            loop {
+                let parent = match subroot.borrow().parent.upgrade() {
+                    Some(parent) => parent,
+                    None => break,
+                };
+                if parent.borrow().color == RBTreeNodeColour::Black {
+                    break;
+                }
+                let grandparent = match parent.borrow().parent.upgrade() {
+                    Some(grandparent) => grandparent,
+                    None => break,
+                };
+
+                let parent_is_left = is_left_child(&grandparent, &parent);
+                let uncle = if parent_is_left { grandparent.borrow().right.clone() } else { grandparent.borrow().left.clone() };
+                let uncle_is_red = uncle.as_ref().is_some_and(|uncle| uncle.borrow().color == RBTreeNodeColour::Red);
+
+                // red uncle: recolour and carry the conflict up to the grandparent
+                if uncle_is_red {
+                    recolour_rb(&parent, RBTreeNodeColour::Black, observers);
+                    recolour_rb(uncle.as_ref().unwrap(), RBTreeNodeColour::Black, observers);
+                    recolour_rb(&grandparent, RBTreeNodeColour::Red, observers);
+                    observers.step(|| write_tree(tree_root));
+                    subroot = grandparent;
+                    continue;
+                }
+
+                // black uncle: rotate the conflict away, a zig-zag needs a second rotation first
+                let subroot_is_left = is_left_child(&parent, &subroot);
+                let case = match (parent_is_left, subroot_is_left) {
+                    (true, true) => BalanceCase::LeftLeft,
+                    (true, false) => BalanceCase::LeftRight,
+                    (false, false) => BalanceCase::RightRight,
+                    (false, true) => BalanceCase::RightLeft,
+                };
+                let grandparent_key = grandparent.borrow().key.clone();
+                observers.emit(|| TreeEvent::Rebalance { case, key: grandparent_key.clone() });
+
+                let mut parent = parent;
+                if parent_is_left != subroot_is_left {
+                    observers.stats.double_rotations += 1;
+                    parent = rotate_rb_at(tree_root, &parent, parent_is_left, observers)?;
+                } else {
+                    observers.stats.single_rotations += 1;
+                }
+                rotate_rb_at(tree_root, &grandparent, !parent_is_left, observers)?;
+                recolour_rb(&parent, RBTreeNodeColour::Black, observers);
+                recolour_rb(&grandparent, RBTreeNodeColour::Red, observers);
+                observers.step(|| write_tree(tree_root));
+                break;
+            }
+
+            if let Some(root) = tree_root.clone() {
+                if recolour_rb(&root, RBTreeNodeColour::Black, observers) {
+                    observers.step(|| write_tree(tree_root));
+                }
+            }
+            Ok(())
+        }
+
+        // walk down to the insertion point, the same walk finds a key that is already there
+        let mut parent: RedBlackTree_Op<T> = None;
+        let mut current = self.clone();
+        while let Some(node) = current {
+            observers.stats.comparisons += 1;
+            current = match node.borrow().key.cmp(&value) {
+                Ordering::Less => node.borrow().right.clone(),
+                Ordering::Greater => node.borrow().left.clone(),
+                Ordering::Equal => return Err(TreeError::DuplicateKey(value.to_string())),
+            };
+            parent = Some(node);
+        }
+
+        let created_tree = RedBlackTree_Op::<T>::new(value.clone()).unwrap();
+        observers.emit(|| TreeEvent::NodeCreated { key: value.clone() });
+        // new nodes start out red, a new root is turned black by the fixup
+        created_tree.borrow_mut().color = RBTreeNodeColour::Red;
+        match parent {
+            None => {
+                *self = Some(created_tree.clone());
+            }
+            Some(parent) => {
+                created_tree.borrow_mut().parent = Rc::downgrade(&parent);
+                if parent.borrow().key < value {
+                    parent.borrow_mut().right = Some(created_tree.clone());
+                } else {
+                    parent.borrow_mut().left = Some(created_tree.clone());
+                }
+            }
+        }
+        observers.step(|| write_tree(self));
+        insert_cl_logic(self, created_tree, observers)
+
+    }
+
+
+
+    fn count_leaves(&self) -> u32 {
+        let mut leaves = 0;
+        let mut stack: Vec<RBTree<T>> = self.iter().cloned().collect();
+        while let Some(node) = stack.pop() {
+            let borrowed_node = node.borrow();
+            if borrowed_node.left.is_none() && borrowed_node.right.is_none() {
+                leaves += 1;
+            }
+            stack.extend(borrowed_node.left.clone());
+            stack.extend(borrowed_node.right.clone());
+        }
+        leaves
+    }
+
+    fn print_traversal(&self) {
+        let mut stack: Vec<RBTree<T>> = Vec::new();
+        let mut current = self.clone();
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node) = current {
+                current = node.borrow().left.clone();
+                stack.push(node);
+            }
+            let node = stack.pop().unwrap();
+            println!("{}", node.borrow().key);
+            current = node.borrow().right.clone();
+        }
+    }
+
+    fn get_tree_height(&self) -> u32 {
+        let mut height = 0;
+        let mut stack: Vec<(RBTree<T>, u32)> = self.iter().map(|node| (node.clone(), 1)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            height = max(height, depth);
+            let borrowed_node = node.borrow();
+            stack.extend(borrowed_node.left.clone().map(|left| (left, depth + 1)));
+            stack.extend(borrowed_node.right.clone().map(|right| (right, depth + 1)));
+        }
+        height
+    }
+
+    fn is_tree_empty(&self) -> bool {
+        self.is_none()
+    }
+
+
+    fn is_node_exists(&self, data: &T) -> bool {
+        self.find_node(data).is_some()
+    }
+
+    fn find_node(&self, data: &T) -> RedBlackTree_Op<T> {
+        search_rb(self, data, &mut 0)
+    }
+
+    // rbtree delete, returns the node now holding the key that came after the deleted one
+    fn delete_node(&mut self, node: &RBTree<T>, observers: &mut TreeObservers<T>) -> Result<RedBlackTree_Op<T>, TreeError> {
+
+        // balancing logic, pushes the missing black up until it can be absorbed
+        fn delete_cl_logic<T: Clone + Ord + Display>(tree_root: &mut RedBlackTree_Op<T>, mut child: RedBlackTree_Op<T>, mut parent: RedBlackTree_Op<T>, mut child_is_left: bool, observers: &mut TreeObservers<T>) -> Result<(), TreeError> {
+
+            while let Some(current_parent) = parent.clone() {
+                if !is_black(&child) {
+                    break;
+                }
+                let sibling_side = if child_is_left { "right" } else { "left" };
+                let sibling_of = |parent: &RBTree<T>| if child_is_left { parent.borrow().right.clone() } else { parent.borrow().left.clone() };
+                let mut sibling = sibling_of(&current_parent).ok_or(TreeError::MissingChild(sibling_side))?;
+
+                // red sibling: rotate it above the parent so the sibling becomes black
+                if sibling.borrow().color == RBTreeNodeColour::Red {
+                    recolour_rb(&sibling, RBTreeNodeColour::Black, observers);
+                    recolour_rb(&current_parent, RBTreeNodeColour::Red, observers);
+                    observers.stats.single_rotations += 1;
+                    rotate_rb_at(tree_root, &current_parent, child_is_left, observers)?;
+                    observers.step(|| write_tree(tree_root));
+                    sibling = sibling_of(&current_parent).ok_or(TreeError::MissingChild(sibling_side))?;
+                }
+
+                let (near, far) = if child_is_left {
+                    (sibling.borrow().left.clone(), sibling.borrow().right.clone())
+                } else {
+                    (sibling.borrow().right.clone(), sibling.borrow().left.clone())
+                };
+
+                // black sibling with black children: recolour and carry the problem up
+                if is_black(&near) && is_black(&far) {
+                    recolour_rb(&sibling, RBTreeNodeColour::Red, observers);
+                    observers.step(|| write_tree(tree_root));
+                    let grandparent = current_parent.borrow().parent.upgrade();
+                    if let Some(grandparent) = &grandparent {
+                        child_is_left = is_left_child(grandparent, &current_parent);
+                    }
+                    child = Some(current_parent);
+                    parent = grandparent;
+                    continue;
+                }
+
+                // near nephew red: turn it into the far one first
+                if is_black(&far) {
+                    recolour_rb(near.as_ref().unwrap(), RBTreeNodeColour::Black, observers);
+                    recolour_rb(&sibling, RBTreeNodeColour::Red, observers);
+                    observers.stats.single_rotations += 1;
+                    rotate_rb_at(tree_root, &sibling, !child_is_left, observers)?;
+                    observers.step(|| write_tree(tree_root));
+                    sibling = sibling_of(&current_parent).ok_or(TreeError::MissingChild(sibling_side))?;
+                }
+
+                // far nephew red: one rotation at the parent settles it
+                let parent_color = current_parent.borrow().color.clone();
+                recolour_rb(&sibling, parent_color, observers);
+                recolour_rb(&current_parent, RBTreeNodeColour::Black, observers);
+                let far = if child_is_left { sibling.borrow().right.clone() } else { sibling.borrow().left.clone() };
+                recolour_rb(far.as_ref().unwrap(), RBTreeNodeColour::Black, observers);
+                observers.stats.single_rotations += 1;
+                rotate_rb_at(tree_root, &current_parent, child_is_left, observers)?;
+                observers.step(|| write_tree(tree_root));
+                child = tree_root.clone();
+                break;
+            }
+
+            if let Some(child) = child {
+                if recolour_rb(&child, RBTreeNodeColour::Black, observers) {
+                    observers.step(|| write_tree(tree_root));
+                }
+            }
+            Ok(())
+        }
+
+        let key = node.borrow().key.clone();
+        observers.emit(|| TreeEvent::NodeRemoved { key: key.clone() });
+
+        // a node with two children swaps keys with its successor, which is then removed instead
+        let (removed, next) = if node.borrow().left.is_some() && node.borrow().right.is_some() {
+            let successor = minimum_node(node.borrow().right.as_ref().unwrap());
+            std::mem::swap(&mut node.borrow_mut().key, &mut successor.borrow_mut().key);
+            (successor, Some(node.clone()))
+        } else {
+            (node.clone(), successor_node(node))
+        };
+
+        // splice the removed node out, it has at most one child
+        let child = if removed.borrow().left.is_some() { removed.borrow_mut().left.take() } else { removed.borrow_mut().right.take() };
+        let parent = removed.borrow().parent.upgrade();
+        if let Some(child) = &child {
+            child.borrow_mut().parent = removed.borrow().parent.clone();
+        }
+        let mut child_is_left = false;
+        match &parent {
+            Some(parent) => {
+                child_is_left = is_left_child(parent, &removed);
+                if child_is_left {
+                    parent.borrow_mut().left = child.clone();
+                } else {
+                    parent.borrow_mut().right = child.clone();
+                }
+            }
+            None => *self = child.clone(),
+        }
+        removed.borrow_mut().parent = Weak::new();
+        observers.step(|| write_tree(self));
+
+        if removed.borrow().color == RBTreeNodeColour::Black {
+            delete_cl_logic(self, child, parent, child_is_left, observers)?;
+        }
+        Ok(next)
+    }
+
+}
+
+
+// rbtree wrapper, keeps track of the size so it never has to be counted
+pub struct RedBlackTree<T> {
+    pub(crate) root: RedBlackTree_Op<T>,
+    pub(crate) len: usize,
+    pub(crate) strict: bool, // report duplicate keys as errors instead of ignoring them
+    pub(crate) observers: TreeObservers<T>,
+    pub(crate) last_stats: TreeStats,
+    pub(crate) total_stats: TreeStats,
+}
+
+impl<T: Clone + Ord + Display> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Ord + Display> RedBlackTree<T> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0, strict: false, observers: TreeObservers::new(), last_stats: TreeStats::default(), total_stats: TreeStats::default() }
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // subscribe to the rotations, recolourings and other structural changes
+    pub fn add_observer(&mut self, observer: Box<dyn TreeObserver<T>>) {
+        self.observers.add(observer);
+    }
+
+    // insert a value, returns false if it was already in the tree
+    pub fn insert(&mut self, value: T) -> Result<bool, TreeError> {
+        let inserted = self.root.insert_node(value, &mut self.observers);
+        self.record_stats();
+        match inserted {
+            Ok(()) => {}
+            Err(TreeError::DuplicateKey(_)) if !self.strict => return Ok(false),
+            Err(err) => return Err(err),
+        }
+        self.len += 1;
+        Ok(true)
+    }
+
+    // remove a value, returns false if it was not in the tree
+    pub fn remove(&mut self, value: T) -> Result<bool, TreeError> {
+        let node = match search_rb(&self.root, &value, &mut self.observers.stats.comparisons) {
+            Some(node) => node,
+            None => {
+                self.record_stats();
+                return Ok(false);
+            }
+        };
+        let removed = self.root.delete_node(&node, &mut self.observers);
+        self.record_stats();
+        removed?;
+        self.len -= 1;
+        Ok(true)
+    }
+
+    // close the counters of the operation that just finished
+    pub(crate) fn record_stats(&mut self) {
+        self.last_stats = self.observers.take_stats();
+        self.total_stats += self.last_stats;
+    }
+
+    // counters of the last insert or remove
+    pub fn last_stats(&self) -> TreeStats {
+        self.last_stats
+    }
+
+    // counters summed over every insert and remove so far
+    pub fn total_stats(&self) -> TreeStats {
+        self.total_stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.last_stats = TreeStats::default();
+        self.total_stats = TreeStats::default();
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.root.is_node_exists(value)
+    }
+
+    // the nodes a search for value passes from the root down, with their colours, the
+    // last one holds the value itself if it is in the tree
+    pub fn search_path(&self, value: &T) -> Vec<(T, RBTreeNodeColour)> {
+        let mut path = Vec::new();
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let borrowed_node = node.borrow();
+            path.push((borrowed_node.key.clone(), borrowed_node.color.clone()));
+            current = if *value < borrowed_node.key {
+                borrowed_node.left.clone()
+            } else if *value > borrowed_node.key {
+                borrowed_node.right.clone()
+            } else {
+                None
+            };
+        }
+        path
+    }
+
+    pub fn count_leaves(&self) -> u32 {
+        self.root.count_leaves()
+    }
+
+    // number of levels, 0 for an empty tree
+    pub fn height(&self) -> u32 {
+        self.root.get_tree_height()
+    }
+
+    // number of nodes in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // remove every node from the tree
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    // keys in sorted order
+    pub fn iter(&self) -> InOrderIter<RBTreeNode<T>> {
+        InOrderIter::new(&self.root)
+    }
+
+    // keys from low to high, both included, in sorted order
+    pub fn range(&self, low: T, high: T) -> RangeIter<RBTreeNode<T>> {
+        RangeIter::new(&self.root, low, high)
+    }
+
+    pub fn pre_order(&self) -> PreOrderIter<RBTreeNode<T>> {
+        PreOrderIter::new(&self.root)
+    }
+
+    pub fn post_order(&self) -> PostOrderIter<RBTreeNode<T>> {
+        PostOrderIter::new(&self.root)
+    }
+
+    // keys level by level, paired with their depth
+    pub fn level_order(&self) -> LevelOrderIter<RBTreeNode<T>> {
+        LevelOrderIter::new(&self.root)
+    }
+
+    // check the red-black rules, the search order, the parent links and the size
+    pub fn validate(&self) -> Result<(), TreeError> {
+        let root = match &self.root {
+            Some(root) => root,
+            None if self.len == 0 => return Ok(()),
+            None => return Err(TreeError::InvariantBroken(format!("empty tree with a size of {}", self.len))),
+        };
+        if root.borrow().color != RBTreeNodeColour::Black {
+            return Err(TreeError::InvariantBroken("the root is red".to_string()));
+        }
+        if root.borrow().parent.upgrade().is_some() {
+            return Err(TreeError::InvariantBroken("the root has a parent".to_string()));
+        }
+
+        let mut count = 0;
+        let mut black_height = None;
+        // nodes to check, with the black nodes above them and the range their key must fall in
+        let mut stack = vec![(root.clone(), 0, None::<T>, None::<T>)];
+        while let Some((node, blacks_above, lower, upper)) = stack.pop() {
+            count += 1;
+            let borrowed_node = node.borrow();
+            let key = borrowed_node.key.clone();
+            if lower.as_ref().is_some_and(|lower| key <= *lower) || upper.as_ref().is_some_and(|upper| key > *upper) {
+                return Err(TreeError::InvariantBroken(format!("{} is out of order", key)));
+            }
+            let blacks = blacks_above + if borrowed_node.color == RBTreeNodeColour::Black { 1 } else { 0 };
+
+            for (child, lower, upper) in [(&borrowed_node.left, lower, Some(key.clone())), (&borrowed_node.right, Some(key.clone()), upper)] {
+                match child {
+                    Some(child) => {
+                        if !child.borrow().parent.upgrade().is_some_and(|parent| Rc::ptr_eq(&parent, &node)) {
+                            return Err(TreeError::InvariantBroken(format!("a child of {} does not point back to it", key)));
+                        }
+                        if borrowed_node.color == RBTreeNodeColour::Red && child.borrow().color == RBTreeNodeColour::Red {
+                            return Err(TreeError::InvariantBroken(format!("red {} has a red child", key)));
+                        }
+                        stack.push((child.clone(), blacks, lower, upper));
+                    }
+                    // every path down to an empty child must see the same number of black nodes
+                    None => {
+                        if *black_height.get_or_insert(blacks) != blacks {
+                            return Err(TreeError::InvariantBroken(format!("uneven black height below {}", key)));
+                        }
+                    }
+                }
+            }
+        }
+        if count != self.len {
+            return Err(TreeError::InvariantBroken(format!("{} nodes but a size of {}", count, self.len)));
+        }
+        Ok(())
+    }
+}
+
+
+pub(crate) fn print_tree<T: Display>(node: &RedBlackTree_Op<T>, depth: usize) {
+    let mut out = String::new();
+    write_tree_at(node, depth, &mut out);
+    print!("{}", out);
+}
+
+// the same drawing as print_tree, collected into a string
+pub(crate) fn write_tree<T: Display>(node: &RedBlackTree_Op<T>) -> String {
+    let mut out = String::new();
+    write_tree_at(node, 0, &mut out);
+    out
+}
+
+// right subtree, node, left subtree, so the drawing reads as the tree turned on its side
+pub(crate) fn write_tree_at<T: Display>(node: &RedBlackTree_Op<T>, depth: usize, out: &mut String) {
+    // nodes whose right side is drawn, waiting for their own line
+    let mut stack: Vec<(RBTree<T>, usize)> = Vec::new();
+    let mut current = node.clone();
+    let mut level = depth;
+    loop {
+        while let Some(n) = current {
+            current = n.borrow().right.clone();
+            stack.push((n, level));
+            level += 1;
+        }
+        let Some((n, n_level)) = stack.pop() else { break };
+        out.push_str(&format!(
+            "{:>1$} [{:?}] {} [{:?}] \n",
+            "",
+            n_level * 5,
+            n.borrow().key,
+            n.borrow().color
+        ));
+        current = n.borrow().left.clone();
+        level = n_level + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_counts_one_comparison_per_node_passed() {
+        let mut tree = RedBlackTree::new();
+        tree.insert(5).unwrap();
+        assert_eq!(tree.last_stats().comparisons, 0);
+        tree.insert(3).unwrap();
+        assert_eq!(tree.last_stats().comparisons, 1);
+        assert!(!tree.insert(5).unwrap());
+        assert_eq!(tree.last_stats().comparisons, 1);
+        assert!(!tree.insert(3).unwrap());
+        assert_eq!(tree.last_stats().comparisons, 2);
+        assert_eq!(tree.len(), 2);
+
+        tree.set_strict(true);
+        assert!(matches!(tree.insert(3), Err(TreeError::DuplicateKey(_))));
+        assert_eq!(tree.len(), 2);
+        tree.validate().unwrap();
+
+        let mut sorted = RedBlackTree::new();
+        for key in 0..1000 {
+            sorted.insert(key).unwrap();
+        }
+        // one walk per insert, searching first and walking again counted 28962
+        assert_eq!(sorted.total_stats().comparisons, 14481);
+    }
+
+    // random inserts and removes against BTreeSet, the small key range makes most removes
+    // hit and walks through every deletion fix-up case many times
+    #[test]
+    fn random_edits_match_btreeset() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::BTreeSet;
+
+        let mut rng = StdRng::seed_from_u64(30);
+        let mut tree = RedBlackTree::new();
+        let mut expected = BTreeSet::new();
+        for _ in 0..20_000 {
+            let key: u32 = rng.gen_range(0..500);
+            if rng.gen_bool(0.5) {
+                assert_eq!(tree.insert(key).unwrap(), expected.insert(key));
+            } else {
+                assert_eq!(tree.remove(key).unwrap(), expected.remove(&key));
+            }
+            tree.validate().unwrap();
+            assert_eq!(tree.len(), expected.len());
+        }
+        assert!(tree.iter().eq(expected.iter().copied()));
+        for key in expected {
+            assert!(tree.remove(key).unwrap());
+            tree.validate().unwrap();
+        }
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+// session files: every tree of a repl session in one versioned json document
+//
+//   {"format": "datatrees-session", "version": 1, "current": "main", "keys": "i64", "trees": [
+//    {"name": "main", "dual": false, "shape": {"tree": "rb", "root": {...}}},
+//    ...]}
+//
+// the shapes are the documents of json.rs, so red-black trees keep their colours and AVL
+// trees their structure and heights. undo histories are not saved.
+
+use crate::dyntree::{DynTree, KeyType, TreeKind};
+use crate::json::{escape_json, parse_json, JsonValue};
+use crate::TreeError;
+
+const FORMAT: &str = "datatrees-session";
+// bump when the layout changes, files from newer versions are turned away
+pub(crate) const VERSION: u32 = 1;
+
+// a tree read back from a session file, its explainer still has to be added
+pub(crate) struct SavedTree {
+    pub(crate) name: String,
+    pub(crate) tree: Box<dyn DynTree>,
+    pub(crate) dual: bool,
+}
+
+// trees are (name, tree, dual mode on) in the order they should be listed
+pub(crate) fn write_session(current: &str, keys: KeyType, trees: &[(&str, &dyn DynTree, bool)]) -> String {
+    let mut json = format!("{{\"format\":{},\"version\":{},\"current\":{},\"keys\":{},\"trees\":[", escape_json(FORMAT), VERSION, escape_json(current), escape_json(keys.name()));
+    for (i, (name, tree, dual)) in trees.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(&format!("\n{{\"name\":{},\"dual\":{},\"shape\":{}}}", escape_json(name), dual, tree.to_json()));
+    }
+    json.push_str("]}\n");
+    json
+}
+
+fn saved_tree(entry: &JsonValue, keys: KeyType) -> Result<SavedTree, TreeError> {
+    let name = entry.get("name").and_then(JsonValue::as_str).ok_or_else(|| TreeError::Parse("tree without a name".to_string()))?;
+    let dual = match entry.get("dual") {
+        Some(JsonValue::Bool(dual)) => *dual,
+        None => false,
+        Some(other) => return Err(TreeError::Parse(format!("dual must be true or false, found {}", other))),
+    };
+    let shape = entry.get("shape").ok_or_else(|| TreeError::Parse(format!("tree {} without a shape", name)))?;
+    let kind = match shape.get("tree").and_then(JsonValue::as_str) {
+        Some(kind) => TreeKind::from_name(kind).ok_or_else(|| TreeError::Parse(format!("unknown tree type {} for {}", kind, name)))?,
+        None => return Err(TreeError::Parse(format!("tree {} without a type", name))),
+    };
+    let mut tree = kind.new_tree(keys);
+    tree.load_json(&shape.to_string())?;
+    Ok(SavedTree { name: name.to_string(), tree, dual })
+}
+
+// the name of the tree in use, the key type and every tree, checked like a loaded shape
+// document
+pub(crate) fn read_session(text: &str) -> Result<(String, KeyType, Vec<SavedTree>), TreeError> {
+    let document = parse_json(text)?;
+    if document.get("format").and_then(JsonValue::as_str) != Some(FORMAT) {
+        return Err(TreeError::Parse("not a datatrees session file".to_string()));
+    }
+    let version = match document.get("version") {
+        Some(JsonValue::Number(version)) => version.parse::<u32>().map_err(|_| TreeError::Parse(format!("invalid version {}", version)))?,
+        _ => return Err(TreeError::Parse("missing version field".to_string())),
+    };
+    if version > VERSION {
+        return Err(TreeError::Parse(format!("session file version {} is newer than this program, which reads up to {}", version, VERSION)));
+    }
+    let keys = match document.get("keys") {
+        Some(JsonValue::String(name)) => KeyType::from_name(name).ok_or_else(|| TreeError::Parse(format!("unknown key type {}", name)))?,
+        _ => return Err(TreeError::Parse("missing keys field".to_string())),
+    };
+
+    let entries = match document.get("trees") {
+        Some(JsonValue::Array(entries)) => entries,
+        _ => return Err(TreeError::Parse("missing trees field".to_string())),
+    };
+    let mut trees: Vec<SavedTree> = Vec::new();
+    for entry in entries {
+        let saved = saved_tree(entry, keys)?;
+        if trees.iter().any(|tree| tree.name == saved.name) {
+            return Err(TreeError::Parse(format!("two trees called {}", saved.name)));
+        }
+        trees.push(saved);
+    }
+    let current = document.get("current").and_then(JsonValue::as_str).ok_or_else(|| TreeError::Parse("missing current field".to_string()))?;
+    if !trees.iter().any(|tree| tree.name == current) {
+        return Err(TreeError::Parse(format!("the current tree {} is not in the file", current)));
+    }
+    Ok((current.to_string(), keys, trees))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree_of(kind: TreeKind, keys: &[&str]) -> Box<dyn DynTree> {
+        let mut tree = kind.new_tree(KeyType::Folded);
+        for key in keys {
+            tree.insert_key(key).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn a_session_comes_back_as_it_was_saved() {
+        let main = tree_of(TreeKind::Rb, &["pear", "Apple", "fig", "kiwi", "lime"]);
+        let other = tree_of(TreeKind::Avl, &["b", "a", "c", "d"]);
+        let empty = tree_of(TreeKind::Avl, &[]);
+        let trees: Vec<(&str, &dyn DynTree, bool)> = vec![("empty", empty.as_ref(), false), ("main", main.as_ref(), true), ("other", other.as_ref(), false)];
+        let text = write_session("main", KeyType::Folded, &trees);
+
+        let (current, keys, saved) = read_session(&text).unwrap();
+        assert_eq!(current, "main");
+        assert_eq!(keys, KeyType::Folded);
+        assert_eq!(saved.len(), trees.len());
+        for (saved, (name, tree, dual)) in saved.iter().zip(&trees) {
+            assert_eq!(saved.name, *name);
+            assert_eq!(saved.dual, *dual);
+            assert_eq!(saved.tree.kind(), tree.kind());
+            // the shape documents hold every key with its colour or height and position
+            assert_eq!(saved.tree.to_json(), tree.to_json());
+        }
+    }
+
+    #[test]
+    fn other_files_and_versions_are_turned_away() {
+        let error = |text: &str| read_session(text).err().expect("the file should have been rejected").to_string();
+        assert!(error(r#"{"format": "something else"}"#).contains("not a datatrees session file"));
+        assert!(error(r#"{"format": "datatrees-session", "version": 2, "current": "main", "keys": "i64", "trees": []}"#).contains("newer than this program"));
+        assert!(error(r#"{"format": "datatrees-session", "version": 1, "current": "main", "trees": []}"#).contains("missing keys field"));
+    }
+}
@@ -0,0 +1,183 @@
+// where the session gets its lines from: the terminal, a pipe or a --script file
+//
+// script format, which also applies to anything piped into stdin:
+//   - one command per line, exactly what would be typed at the prompt; empty lines are
+//     skipped
+//   - lines starting with // are comments and are skipped
+//   - lines starting with # are directives:
+//       #echo on|off     print every line as it is read, so the output shows the commands
+//                        (not with --json, where the output is json objects only)
+//       #strict on|off   stop with a non-zero exit status at the first failing command
+//       #end             stop reading, as if the input ended here
+//   - the end of the input ends the session cleanly, no trailing quit needed
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal};
+
+use crate::TreeError;
+
+enum Source {
+    // read through the shared stdin buffer, so the explain pauses see the same lines
+    Stdin,
+    Reader(Box<dyn BufRead>), // a --script file
+}
+
+pub struct ReplInput {
+    source: Source,
+    name: String, // shown in front of the line number in errors
+    line: usize,
+    echo: bool,
+    strict: bool,
+    finished: bool,
+    errors: Option<Vec<String>>, // complaints kept for the json output instead of printed
+}
+
+impl ReplInput {
+    pub fn stdin(strict: bool) -> Self {
+        Self { source: Source::Stdin, name: "stdin".to_string(), line: 0, echo: false, strict, finished: false, errors: None }
+    }
+
+    pub fn script(path: &str, strict: bool) -> Result<Self, TreeError> {
+        let file = File::open(path).map_err(|err| TreeError::Io(io::Error::new(err.kind(), format!("{}: {}", path, err))))?;
+        Ok(Self::reader(path, BufReader::new(file), strict))
+    }
+
+    // lines from anything readable, name is what errors show in front of the line number
+    pub(crate) fn reader(name: &str, reader: impl BufRead + 'static, strict: bool) -> Self {
+        Self { source: Source::Reader(Box::new(reader)), name: name.to_string(), line: 0, echo: false, strict, finished: false, errors: None }
+    }
+
+    // keep complaints for take_errors from now on, for the json output
+    pub fn keep_errors(&mut self) {
+        self.errors.get_or_insert_with(Vec::new);
+    }
+
+    // the complaints since the last call, empty unless keep_errors was called
+    pub fn take_errors(&mut self) -> Vec<String> {
+        self.errors.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors.as_ref().is_some_and(|errors| !errors.is_empty())
+    }
+
+    // someone is typing, so a prompt is worth showing
+    pub fn is_interactive(&self) -> bool {
+        (matches!(self.source, Source::Stdin) && io::stdin().is_terminal())
+    }
+
+    // the next line without its line ending, None once the input is used up
+    pub fn next_line(&mut self) -> Result<Option<String>, TreeError> {
+        while !self.finished {
+            let mut raw = String::new();
+            let read = match &mut self.source {
+                Source::Stdin => io::stdin().read_line(&mut raw)?,
+                Source::Reader(reader) => reader.read_line(&mut raw)?,
+            };
+            if read == 0 {
+                self.finished = true;
+                break;
+            }
+            self.line += 1;
+            let line = raw.trim_end_matches(['\n', '\r']);
+            let trimmed = line.trim();
+            if trimmed.starts_with("//") {
+                continue;
+            }
+            if let Some(directive) = trimmed.strip_prefix('#') {
+                self.directive(directive)?;
+                continue;
+            }
+            if self.echo && self.errors.is_none() {
+                println!("> {}", line);
+            }
+            return Ok(Some(line.to_string()));
+        }
+        Ok(None)
+    }
+
+    fn directive(&mut self, directive: &str) -> Result<(), TreeError> {
+        let words: Vec<&str> = directive.split_whitespace().collect();
+        match words.as_slice() {
+            ["echo", "on"] => self.echo = true,
+            ["echo", "off"] => self.echo = false,
+            ["strict", "on"] => self.strict = true,
+            ["strict", "off"] => self.strict = false,
+            ["end"] => self.finished = true,
+            _ => self.complain(&format!("unknown directive #{}", directive.trim()))?,
+        }
+        Ok(())
+    }
+
+    // report a failed command, in strict mode it also ends the session with an error
+    pub fn complain(&mut self, message: &str) -> Result<(), TreeError> {
+        match &mut self.errors {
+            Some(errors) => errors.push(message.to_string()),
+            None => println!("{}", message),
+        }
+        if self.strict {
+            return Err(TreeError::Command(format!("{}:{}: {}", self.name, self.line, message)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(text: &'static str) -> ReplInput {
+        ReplInput::reader("test.txt", text.as_bytes(), false)
+    }
+
+    // every line the session would get, up to the end of the input
+    fn lines(input: &mut ReplInput) -> Vec<String> {
+        std::iter::from_fn(|| input.next_line().unwrap()).collect()
+    }
+
+    #[test]
+    fn comments_are_skipped_and_the_input_ends_cleanly() {
+        let mut input = script("insert 1\n// a comment\n  // indented too\n\nfind 1\r\nsize");
+        assert_eq!(lines(&mut input), ["insert 1", "", "find 1", "size"]);
+        assert_eq!(input.next_line().unwrap(), None);
+        assert!(!input.is_interactive());
+    }
+
+    #[test]
+    fn end_stops_reading() {
+        let mut input = script("insert 1\n#end\ninsert 2\n");
+        assert_eq!(lines(&mut input), ["insert 1"]);
+        assert_eq!(input.next_line().unwrap(), None);
+    }
+
+    #[test]
+    fn echo_is_switched_by_its_directive() {
+        let mut input = script("#echo on\nsize\n  #echo off\nheight\n");
+        assert_eq!(input.next_line().unwrap().as_deref(), Some("size"));
+        assert!(input.echo);
+        assert_eq!(input.next_line().unwrap().as_deref(), Some("height"));
+        assert!(!input.echo);
+    }
+
+    #[test]
+    fn strict_errors_name_the_file_and_line() {
+        let mut input = script("insert 1\n#strict on\ninsert 1\n");
+        input.next_line().unwrap();
+        assert!(input.complain("1 is already in the tree").is_ok());
+        input.next_line().unwrap();
+        let err = input.complain("1 is already in the tree").unwrap_err();
+        assert_eq!(err.to_string(), "command failed: test.txt:3: 1 is already in the tree");
+    }
+
+    #[test]
+    fn unknown_directives_are_complaints() {
+        let mut input = script("#bogus\nsize\n");
+        input.keep_errors();
+        assert_eq!(input.next_line().unwrap().as_deref(), Some("size"));
+        assert_eq!(input.take_errors(), ["unknown directive #bogus"]);
+
+        let mut input = script("#strict on\n#bogus\nsize\n");
+        input.keep_errors();
+        assert_eq!(input.next_line().unwrap_err().to_string(), "command failed: test.txt:2: unknown directive #bogus");
+    }
+}
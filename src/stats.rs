@@ -0,0 +1,40 @@
+// operation counters, kept by both trees for the last operation and in total
+
+use std::fmt;
+use std::ops::AddAssign;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TreeStats {
+    pub comparisons: u64, // key comparisons while walking down, one per node visited
+    pub single_rotations: u64,
+    pub double_rotations: u64, // a zig-zag fixed with two rotations counts once here
+    pub colour_flips: u64,
+    pub height_updates: u64, // every time an avl height is recomputed, changed or not
+}
+
+impl TreeStats {
+    // rotations actually done, a double rotation is two of them
+    pub fn rotations(&self) -> u64 {
+        self.single_rotations + 2 * self.double_rotations
+    }
+}
+
+impl AddAssign for TreeStats {
+    fn add_assign(&mut self, other: Self) {
+        self.comparisons += other.comparisons;
+        self.single_rotations += other.single_rotations;
+        self.double_rotations += other.double_rotations;
+        self.colour_flips += other.colour_flips;
+        self.height_updates += other.height_updates;
+    }
+}
+
+impl fmt::Display for TreeStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "comparisons: {}, single rotations: {}, double rotations: {}, colour flips: {}, height updates: {}",
+            self.comparisons, self.single_rotations, self.double_rotations, self.colour_flips, self.height_updates
+        )
+    }
+}
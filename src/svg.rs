@@ -0,0 +1,282 @@
+// self-contained svg rendering for both trees
+//
+// nodes are laid out with the Reingold-Tilford idea: every subtree is placed on its own,
+// then the left and right subtrees of a node are pushed apart until their contours
+// (the outermost x on each level) no longer come closer than one unit, and the node
+// sits centred above them. nodes are numbered in pre-order, so every child comes after
+// its parent and walking the numbers backwards visits subtrees before their roots.
+
+use std::cell::RefCell;
+use std::fmt::{Display, Write};
+use std::rc::Rc;
+
+use crate::traversal::BinaryNode;
+#[cfg(feature = "avl")]
+use crate::avl::{AVL_Tree, AVL_Tree_Node};
+#[cfg(feature = "rb")]
+use crate::rb::{RBTreeNode, RedBlackTree};
+use crate::RBTreeNodeColour;
+
+const UNIT: f64 = 44.0; // horizontal space between neighbouring nodes
+const LEVEL: f64 = 70.0; // vertical space between levels
+const RADIUS: f64 = 18.0;
+const MARGIN: f64 = 30.0;
+
+// how a single node is drawn
+struct NodeStyle {
+    label: String,
+    fill: &'static str,
+    text: &'static str,
+    note: Option<String>, // small annotation under the node
+}
+
+struct LayoutNode {
+    style: NodeStyle,
+    depth: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+    offset: f64, // x relative to the parent
+    x: f64,
+    // leftmost and rightmost x on each level of the subtree, relative to this node
+    contour: Vec<(f64, f64)>,
+}
+
+// escape text for use inside svg markup
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// number the nodes in pre-order and remember how they link up
+fn flatten<N: BinaryNode>(root: &Option<Rc<RefCell<N>>>, style: &impl Fn(&N) -> NodeStyle) -> Vec<LayoutNode> {
+    let mut nodes: Vec<LayoutNode> = Vec::new();
+    // nodes to number, with their parent index, whether they hang off its left, and their depth
+    type Pending<N> = (Rc<RefCell<N>>, Option<(usize, bool)>, usize);
+    let mut stack: Vec<Pending<N>> = root.iter().map(|node| (node.clone(), None, 0)).collect();
+    while let Some((node, parent, depth)) = stack.pop() {
+        let index = nodes.len();
+        let borrowed_node = node.borrow();
+        nodes.push(LayoutNode { style: style(&borrowed_node), depth, left: None, right: None, offset: 0.0, x: 0.0, contour: Vec::new() });
+        match parent {
+            Some((parent, true)) => nodes[parent].left = Some(index),
+            Some((parent, false)) => nodes[parent].right = Some(index),
+            None => {}
+        }
+        stack.extend(borrowed_node.right().map(|right| (right, Some((index, false)), depth + 1)));
+        stack.extend(borrowed_node.left().map(|left| (left, Some((index, true)), depth + 1)));
+    }
+    nodes
+}
+
+// shift a child contour by offset and append it below the node's own level
+fn merge_contour(contour: &mut Vec<(f64, f64)>, child: &[(f64, f64)], offset: f64) {
+    for (level, (low, high)) in child.iter().enumerate() {
+        match contour.get_mut(level + 1) {
+            Some(existing) => {
+                existing.0 = existing.0.min(low + offset);
+                existing.1 = existing.1.max(high + offset);
+            }
+            None => contour.push((low + offset, high + offset)),
+        }
+    }
+}
+
+fn layout(nodes: &mut [LayoutNode]) {
+    for index in (0..nodes.len()).rev() {
+        let left_contour = nodes[index].left.map(|left| std::mem::take(&mut nodes[left].contour));
+        let right_contour = nodes[index].right.map(|right| std::mem::take(&mut nodes[right].contour));
+
+        // a lone child still leans to its side so left and right stay readable
+        let half_gap = match (&left_contour, &right_contour) {
+            (Some(left), Some(right)) => {
+                let closest = left.iter().zip(right.iter()).map(|(l, r)| l.1 - r.0).fold(f64::MIN, f64::max);
+                (closest + 1.0).max(1.0) / 2.0
+            }
+            _ => 0.5,
+        };
+
+        let mut contour = vec![(0.0, 0.0)];
+        if let (Some(left), Some(child)) = (nodes[index].left, &left_contour) {
+            nodes[left].offset = -half_gap;
+            merge_contour(&mut contour, child, -half_gap);
+        }
+        if let (Some(right), Some(child)) = (nodes[index].right, &right_contour) {
+            nodes[right].offset = half_gap;
+            merge_contour(&mut contour, child, half_gap);
+        }
+        nodes[index].contour = contour;
+    }
+
+    // offsets become absolute positions from the root down
+    for index in 0..nodes.len() {
+        let x = nodes[index].x;
+        for child in [nodes[index].left, nodes[index].right].into_iter().flatten() {
+            nodes[child].x = x + nodes[child].offset;
+        }
+    }
+}
+
+fn render<N: BinaryNode>(root: &Option<Rc<RefCell<N>>>, style: impl Fn(&N) -> NodeStyle) -> String {
+    let mut nodes = flatten(root, &style);
+    layout(&mut nodes);
+
+    let min_x = nodes.iter().map(|node| node.x).fold(0.0, f64::min);
+    let max_x = nodes.iter().map(|node| node.x).fold(0.0, f64::max);
+    let max_depth = nodes.iter().map(|node| node.depth).max().unwrap_or(0);
+    let position = |node: &LayoutNode| ((node.x - min_x) * UNIT + MARGIN + RADIUS, node.depth as f64 * LEVEL + MARGIN + RADIUS);
+    let width = (max_x - min_x) * UNIT + 2.0 * (MARGIN + RADIUS);
+    let height = max_depth as f64 * LEVEL + 2.0 * (MARGIN + RADIUS);
+
+    let mut svg = String::new();
+    writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\">", width, height, width, height).unwrap();
+    writeln!(svg, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap();
+
+    // edges first so the circles are drawn over them
+    for node in &nodes {
+        let (x1, y1) = position(node);
+        for child in [node.left, node.right].into_iter().flatten() {
+            let (x2, y2) = position(&nodes[child]);
+            writeln!(svg, "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#555\" stroke-width=\"2\"/>", x1, y1, x2, y2).unwrap();
+        }
+    }
+    for node in &nodes {
+        let (x, y) = position(node);
+        writeln!(svg, "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"#222\" stroke-width=\"1.5\"/>", x, y, RADIUS, node.style.fill).unwrap();
+        writeln!(svg, "  <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" font-family=\"Helvetica, sans-serif\" font-size=\"13\" fill=\"{}\">{}</text>", x, y, node.style.text, escape_xml(&node.style.label)).unwrap();
+        if let Some(note) = &node.style.note {
+            writeln!(svg, "  <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-family=\"Helvetica, sans-serif\" font-size=\"10\" fill=\"#333\">{}</text>", x, y + RADIUS + 11.0, escape_xml(note)).unwrap();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(feature = "rb")]
+impl<T: Clone + Display> RedBlackTree<T> {
+    // svg drawing with every node in its colour
+    pub fn to_svg(&self) -> String {
+        render(&self.root, |node: &RBTreeNode<T>| NodeStyle {
+            label: node.key.to_string(),
+            fill: if node.color == RBTreeNodeColour::Red { "#d62728" } else { "#222222" },
+            text: "white",
+            note: None,
+        })
+    }
+}
+
+#[cfg(feature = "avl")]
+impl<T: Clone + Display> AVL_Tree<T> {
+    // svg drawing with the height next to every node
+    pub fn to_svg(&self) -> String {
+        render(&self.root, |node: &AVL_Tree_Node<T>| NodeStyle {
+            label: node.value.to_string(),
+            fill: "#aec7e8",
+            text: "black",
+            note: Some(format!("h={}", node.height)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the laid out nodes of a tree, the drawing style does not matter for the positions
+    fn placed<N: BinaryNode>(root: &Option<Rc<RefCell<N>>>) -> Vec<LayoutNode> {
+        let mut nodes = flatten(root, &|_: &N| NodeStyle { label: String::new(), fill: "", text: "", note: None });
+        layout(&mut nodes);
+        nodes
+    }
+
+    // the outermost x on each level below a node, leftmost or rightmost
+    fn outline(nodes: &[LayoutNode], top: usize, rightmost: bool) -> Vec<f64> {
+        let mut outline: Vec<f64> = Vec::new();
+        let mut stack = vec![top];
+        while let Some(index) = stack.pop() {
+            let level = nodes[index].depth - nodes[top].depth;
+            let x = nodes[index].x;
+            match outline.get_mut(level) {
+                Some(edge) if rightmost => *edge = edge.max(x),
+                Some(edge) => *edge = edge.min(x),
+                None => outline.push(x),
+            }
+            stack.extend([nodes[index].left, nodes[index].right].into_iter().flatten());
+        }
+        outline
+    }
+
+    // sibling subtrees keep at least a unit apart on every level they share, and every
+    // node sits centred over its children
+    fn check_layout(nodes: &[LayoutNode]) {
+        for (index, node) in nodes.iter().enumerate() {
+            match (node.left, node.right) {
+                (Some(left), Some(right)) => {
+                    let left_edge = outline(nodes, left, true);
+                    let right_edge = outline(nodes, right, false);
+                    for (level, (l, r)) in left_edge.iter().zip(&right_edge).enumerate() {
+                        assert!(r - l >= 1.0 - 1e-9, "node {} level {}: {} and {} overlap", index, level, l, r);
+                    }
+                    assert!((nodes[left].x + nodes[right].x - 2.0 * node.x).abs() < 1e-9, "node {} is off centre", index);
+                }
+                (Some(child), None) => assert_eq!(nodes[child].x, node.x - 0.5),
+                (None, Some(child)) => assert_eq!(nodes[child].x, node.x + 0.5),
+                (None, None) => {}
+            }
+        }
+        // so no two nodes on a level share a spot
+        let deepest = nodes.iter().map(|node| node.depth).max().unwrap_or(0);
+        for depth in 0..=deepest {
+            let mut xs: Vec<f64> = nodes.iter().filter(|node| node.depth == depth).map(|node| node.x).collect();
+            xs.sort_by(f64::total_cmp);
+            assert!(xs.windows(2).all(|pair| pair[1] - pair[0] >= 1.0 - 1e-9), "level {}: {:?}", depth, xs);
+        }
+    }
+
+    // every key below 101 once, in a scrambled order
+    fn scrambled() -> impl Iterator<Item = i32> {
+        (0..101).map(|step| step * 37 % 101)
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn red_black_subtrees_do_not_overlap() {
+        for keys in [scrambled().collect::<Vec<_>>(), (0..100).collect(), (0..100).rev().collect()] {
+            let mut tree = RedBlackTree::new();
+            for key in keys {
+                tree.insert(key).unwrap();
+            }
+            let nodes = placed(&tree.root);
+            assert_eq!(nodes.len(), tree.len());
+            check_layout(&nodes);
+        }
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn avl_subtrees_do_not_overlap() {
+        for keys in [scrambled().collect::<Vec<_>>(), (0..100).collect()] {
+            let mut tree = AVL_Tree::new();
+            for key in keys {
+                tree.insert(key).unwrap();
+            }
+            let nodes = placed(&tree.root);
+            assert_eq!(nodes.len(), tree.len());
+            check_layout(&nodes);
+        }
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn every_node_is_drawn_with_its_label_escaped() {
+        let mut tree = RedBlackTree::new();
+        for word in ["m", "<a&b>", "z"] {
+            tree.insert(crate::StringKey(word.to_string())).unwrap();
+        }
+        let svg = tree.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert!(svg.contains(">&quot;&lt;a&amp;b&gt;&quot;</text>"));
+        // an empty tree is just the background
+        assert_eq!(RedBlackTree::<i32>::new().to_svg().lines().count(), 3);
+    }
+}
@@ -0,0 +1,271 @@
+// traversal iterators shared by the red-black and AVL trees
+// each iterator keeps its own stack/queue of nodes so deep trees never recurse
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+#[cfg(feature = "avl")]
+use crate::avl::AVL_Tree_Node;
+#[cfg(feature = "rb")]
+use crate::rb::RBTreeNode;
+
+// what the iterators need to know about a node
+pub trait BinaryNode: Sized {
+    type Key: Clone;
+    fn key(&self) -> Self::Key;
+    fn left(&self) -> Option<Rc<RefCell<Self>>>;
+    fn right(&self) -> Option<Rc<RefCell<Self>>>;
+}
+
+#[cfg(feature = "rb")]
+impl<T: Clone> BinaryNode for RBTreeNode<T> {
+    type Key = T;
+    fn key(&self) -> T {
+        self.key.clone()
+    }
+    fn left(&self) -> Option<Rc<RefCell<Self>>> {
+        self.left.clone()
+    }
+    fn right(&self) -> Option<Rc<RefCell<Self>>> {
+        self.right.clone()
+    }
+}
+
+#[cfg(feature = "avl")]
+impl<T: Clone> BinaryNode for AVL_Tree_Node<T> {
+    type Key = T;
+    fn key(&self) -> T {
+        self.value.clone()
+    }
+    fn left(&self) -> Option<Rc<RefCell<Self>>> {
+        self.left.clone()
+    }
+    fn right(&self) -> Option<Rc<RefCell<Self>>> {
+        self.right.clone()
+    }
+}
+
+// left subtree, node, right subtree
+pub struct InOrderIter<N> {
+    stack: Vec<Rc<RefCell<N>>>,
+    current: Option<Rc<RefCell<N>>>,
+}
+
+impl<N: BinaryNode> InOrderIter<N> {
+    pub(crate) fn new(root: &Option<Rc<RefCell<N>>>) -> Self {
+        Self { stack: Vec::new(), current: root.clone() }
+    }
+}
+
+impl<N: BinaryNode> Iterator for InOrderIter<N> {
+    type Item = N::Key;
+
+    fn next(&mut self) -> Option<N::Key> {
+        // go as far left as possible before handing out a key
+        while let Some(node) = self.current.take() {
+            self.current = node.borrow().left();
+            self.stack.push(node);
+        }
+        let node = self.stack.pop()?;
+        self.current = node.borrow().right();
+        return Some(node.borrow().key());
+    }
+}
+
+// node, left subtree, right subtree
+pub struct PreOrderIter<N> {
+    stack: Vec<Rc<RefCell<N>>>,
+}
+
+impl<N: BinaryNode> PreOrderIter<N> {
+    pub(crate) fn new(root: &Option<Rc<RefCell<N>>>) -> Self {
+        Self { stack: root.iter().cloned().collect() }
+    }
+}
+
+impl<N: BinaryNode> Iterator for PreOrderIter<N> {
+    type Item = N::Key;
+
+    fn next(&mut self) -> Option<N::Key> {
+        let node = self.stack.pop()?;
+        let borrowed_node = node.borrow();
+        // right goes on first so the left subtree comes out first
+        self.stack.extend(borrowed_node.right());
+        self.stack.extend(borrowed_node.left());
+        Some(borrowed_node.key())
+    }
+}
+
+// left subtree, right subtree, node
+pub struct PostOrderIter<N> {
+    // each node is visited twice, the flag says whether its children are already on the stack
+    stack: Vec<(Rc<RefCell<N>>, bool)>,
+}
+
+impl<N: BinaryNode> PostOrderIter<N> {
+    pub(crate) fn new(root: &Option<Rc<RefCell<N>>>) -> Self {
+        Self { stack: root.iter().map(|node| (node.clone(), false)).collect() }
+    }
+}
+
+impl<N: BinaryNode> Iterator for PostOrderIter<N> {
+    type Item = N::Key;
+
+    fn next(&mut self) -> Option<N::Key> {
+        while let Some((node, expanded)) = self.stack.pop() {
+            if expanded {
+                return Some(node.borrow().key());
+            }
+            let left = node.borrow().left();
+            let right = node.borrow().right();
+            self.stack.push((node, true));
+            self.stack.extend(right.map(|right| (right, false)));
+            self.stack.extend(left.map(|left| (left, false)));
+        }
+        None
+    }
+}
+
+// keys between two bounds, both included, in sorted order
+// subtrees that lie wholly outside the bounds are never visited
+pub struct RangeIter<N: BinaryNode> {
+    stack: Vec<Rc<RefCell<N>>>,
+    current: Option<Rc<RefCell<N>>>,
+    low: N::Key,
+    high: N::Key,
+}
+
+impl<N: BinaryNode> RangeIter<N> {
+    pub(crate) fn new(root: &Option<Rc<RefCell<N>>>, low: N::Key, high: N::Key) -> Self {
+        Self { stack: Vec::new(), current: root.clone(), low, high }
+    }
+}
+
+impl<N: BinaryNode> Iterator for RangeIter<N> where N::Key: Ord {
+    type Item = N::Key;
+
+    fn next(&mut self) -> Option<N::Key> {
+        // go left as far as possible, nodes below the low bound only lead right
+        while let Some(node) = self.current.take() {
+            if node.borrow().key() < self.low {
+                self.current = node.borrow().right();
+            } else {
+                self.current = node.borrow().left();
+                self.stack.push(node);
+            }
+        }
+        let node = self.stack.pop()?;
+        let key = node.borrow().key();
+        if key > self.high {
+            self.stack.clear();
+            return None;
+        }
+        self.current = node.borrow().right();
+        Some(key)
+    }
+}
+
+// breadth first, top level down, yields (depth, key) with the root at depth 0
+pub struct LevelOrderIter<N> {
+    queue: VecDeque<(Rc<RefCell<N>>, usize)>,
+}
+
+impl<N: BinaryNode> LevelOrderIter<N> {
+    pub(crate) fn new(root: &Option<Rc<RefCell<N>>>) -> Self {
+        Self { queue: root.iter().map(|node| (node.clone(), 0)).collect() }
+    }
+}
+
+impl<N: BinaryNode> Iterator for LevelOrderIter<N> {
+    type Item = (usize, N::Key);
+
+    fn next(&mut self) -> Option<(usize, N::Key)> {
+        let (node, depth) = self.queue.pop_front()?;
+        let borrowed_node = node.borrow();
+        self.queue.extend(borrowed_node.left().map(|left| (left, depth + 1)));
+        self.queue.extend(borrowed_node.right().map(|right| (right, depth + 1)));
+        Some((depth, borrowed_node.key()))
+    }
+}
+
+// print a level-order walk with one line per level
+pub fn print_levels<K: std::fmt::Display>(levels: impl Iterator<Item = (usize, K)>) {
+    let mut current_depth = None;
+    for (depth, key) in levels {
+        if current_depth != Some(depth) {
+            if current_depth.is_some() {
+                println!();
+            }
+            print!("level {}:", depth);
+            current_depth = Some(depth);
+        }
+        print!(" {}", key);
+    }
+    if current_depth.is_some() {
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a chain this deep overflows the 2 MiB stack of a test thread if dropping or walking
+    // it recurses once per node
+    const DEPTH: u32 = 200_000;
+
+    // every walk over a chain leaning right, holding 0 to DEPTH - 1 from the root down
+    fn check_chain<N: BinaryNode<Key = u32>>(root: &Option<Rc<RefCell<N>>>) {
+        assert!(InOrderIter::new(root).eq(0..DEPTH));
+        assert!(PreOrderIter::new(root).eq(0..DEPTH));
+        assert!(PostOrderIter::new(root).eq((0..DEPTH).rev()));
+        assert!(LevelOrderIter::new(root).eq((0..DEPTH).map(|key| (key as usize, key))));
+        assert!(RangeIter::new(root, 10, DEPTH - 10).eq(10..=DEPTH - 10));
+    }
+
+    #[cfg(feature = "rb")]
+    #[test]
+    fn a_long_red_black_chain() {
+        use crate::rb::{RedBlackTree, RedBlackTree_Op};
+        use crate::RBTreeNodeColour;
+
+        let mut root: RedBlackTree_Op<u32> = None;
+        for key in (0..DEPTH).rev() {
+            let node = RBTreeNode::new(key, RBTreeNodeColour::Black);
+            if let Some(child) = root.take() {
+                child.borrow_mut().parent = Rc::downgrade(&node);
+                node.borrow_mut().right = Some(child);
+            }
+            root = Some(node);
+        }
+        let tree = RedBlackTree { root, len: DEPTH as usize, ..RedBlackTree::new() };
+
+        check_chain(&tree.root);
+        assert_eq!(tree.count_leaves(), 1);
+        assert_eq!(tree.height(), DEPTH);
+        drop(tree);
+    }
+
+    #[cfg(feature = "avl")]
+    #[test]
+    fn a_long_avl_chain() {
+        use crate::avl::AVL_Tree;
+
+        let mut root: Option<Rc<RefCell<AVL_Tree_Node<u32>>>> = None;
+        for key in (0..DEPTH).rev() {
+            let node = AVL_Tree_Node::new(key);
+            if let Some(child) = root.take() {
+                node.borrow_mut().height = child.borrow().height + 1;
+                node.borrow_mut().right = Some(child);
+            }
+            root = Some(node);
+        }
+        let tree = AVL_Tree { root, len: DEPTH as usize, ..AVL_Tree::new() };
+
+        check_chain(&tree.root);
+        assert_eq!(tree.count_leaves(), 1);
+        assert_eq!(tree.height(), DEPTH as i32);
+        drop(tree);
+    }
+}
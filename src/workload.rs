@@ -0,0 +1,205 @@
+// generated keys to fill a tree with: distinct random keys from a seed, and the orders
+// that show off the best and worst cases of each tree
+//
+//   random COUNT [--seed N] [--range LOW..HIGH]     HIGH is left out, like a rust range
+//   sequence sorted|reverse|zigzag COUNT           0 to COUNT-1, zigzag goes 0, COUNT-1, 1, ...
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// far more than anyone wants to watch being inserted, but it keeps a typo from eating memory
+const MAX_COUNT: usize = 1_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Order {
+    Sorted,
+    Reverse,
+    ZigZag,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Workload {
+    Random { count: usize, seed: Option<u64>, low: i64, high: i64 },
+    Sequence { order: Order, count: usize },
+}
+
+fn parse_count(word: Option<&String>, usage: &str) -> Result<usize, String> {
+    let word = word.ok_or(format!("usage: {}", usage))?;
+    match word.parse::<usize>() {
+        Ok(0) => Err("the count must be at least 1".to_string()),
+        Ok(count) if count <= MAX_COUNT => Ok(count),
+        Ok(_) => Err(format!("count {} is above the limit of {}", word, MAX_COUNT)),
+        Err(_) => Err(format!("'{}' is not a count", word)),
+    }
+}
+
+// the words of a random or sequence command, starting with its name
+pub fn parse_workload(words: &[String]) -> Result<Workload, String> {
+    const RANDOM_USAGE: &str = "random COUNT [--seed N] [--range LOW..HIGH]";
+    const SEQUENCE_USAGE: &str = "sequence sorted|reverse|zigzag COUNT";
+    match words.first().map(String::as_str) {
+        Some("random") => {
+            let count = parse_count(words.get(1), RANDOM_USAGE)?;
+            let mut seed = None;
+            let mut range = None;
+            let mut options = words[2..].iter();
+            while let Some(option) = options.next() {
+                let value = options.next().ok_or(format!("{} needs a value", option))?;
+                match option.as_str() {
+                    "--seed" => seed = Some(value.parse::<u64>().map_err(|_| format!("'{}' is not a seed", value))?),
+                    "--range" => range = Some(parse_range(value)?),
+                    _ => return Err(format!("usage: {}", RANDOM_USAGE)),
+                }
+            }
+            // ten times as many keys as asked for keeps the picks spread out
+            let (low, high) = range.unwrap_or((0, count as i64 * 10));
+            if high.abs_diff(low) < count as u64 {
+                return Err(format!("the range {}..{} holds fewer than {} keys", low, high, count));
+            }
+            Ok(Workload::Random { count, seed, low, high })
+        }
+        Some("sequence") => {
+            let order = match words.get(1).map(String::as_str) {
+                Some("sorted") => Order::Sorted,
+                Some("reverse") => Order::Reverse,
+                Some("zigzag") => Order::ZigZag,
+                Some(other) => return Err(format!("unknown order '{}', expected sorted, reverse or zigzag", other)),
+                None => return Err(format!("usage: {}", SEQUENCE_USAGE)),
+            };
+            let count = parse_count(words.get(2), SEQUENCE_USAGE)?;
+            if words.len() > 3 {
+                return Err(format!("usage: {}", SEQUENCE_USAGE));
+            }
+            Ok(Workload::Sequence { order, count })
+        }
+        _ => Err(format!("usage: {} or {}", RANDOM_USAGE, SEQUENCE_USAGE)),
+    }
+}
+
+fn parse_range(text: &str) -> Result<(i64, i64), String> {
+    let bounds = text.split_once("..").and_then(|(low, high)| Some((low.parse::<i64>().ok()?, high.parse::<i64>().ok()?)));
+    match bounds {
+        Some((low, high)) if low < high => Ok((low, high)),
+        Some(_) => Err(format!("the range {} is empty", text)),
+        None => Err(format!("'{}' is not a range like 0..100", text)),
+    }
+}
+
+impl Workload {
+    // pick a seed for a random workload that has none, so it can be shown and run again
+    pub fn with_seed(self) -> Workload {
+        match self {
+            Workload::Random { count, seed: None, low, high } => Workload::Random { count, seed: Some(rand::random()), low, high },
+            workload => workload,
+        }
+    }
+
+    // the smallest and the largest key the workload can produce
+    pub fn bounds(&self) -> (i64, i64) {
+        match *self {
+            Workload::Random { low, high, .. } => (low, high - 1),
+            Workload::Sequence { count, .. } => (0, count as i64 - 1),
+        }
+    }
+
+    // the keys in the order they should be inserted
+    pub fn keys(&self) -> Vec<i64> {
+        match *self {
+            Workload::Random { count, seed, low, high } => {
+                let mut rng = match seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_entropy(),
+                };
+                let picks = rand::seq::index::sample(&mut rng, high.abs_diff(low) as usize, count);
+                picks.into_iter().map(|pick| low.wrapping_add(pick as i64)).collect()
+            }
+            Workload::Sequence { order: Order::Sorted, count } => (0..count as i64).collect(),
+            Workload::Sequence { order: Order::Reverse, count } => (0..count as i64).rev().collect(),
+            Workload::Sequence { order: Order::ZigZag, count } => {
+                let count = count as i64;
+                // smallest, largest, second smallest, ... with the middle one last for odd counts
+                (0..count / 2).flat_map(|low| [low, count - 1 - low]).chain((count % 2 == 1).then_some(count / 2)).collect()
+            }
+        }
+    }
+}
+
+// the command that produces the same keys again
+impl std::fmt::Display for Workload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Workload::Random { count, seed: Some(seed), low, high } => write!(f, "random {} --seed {} --range {}..{}", count, seed, low, high),
+            Workload::Random { count, seed: None, low, high } => write!(f, "random {} --range {}..{}", count, low, high),
+            Workload::Sequence { order, count } => {
+                let order = match order {
+                    Order::Sorted => "sorted",
+                    Order::Reverse => "reverse",
+                    Order::ZigZag => "zigzag",
+                };
+                write!(f, "sequence {} {}", order, count)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workload(line: &str) -> Workload {
+        let words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        parse_workload(&words).unwrap()
+    }
+
+    #[test]
+    fn a_seed_gives_the_same_keys_every_time() {
+        let random = workload("random 200 --seed 9");
+        assert_eq!(random.keys(), random.keys());
+        assert_eq!(random.keys(), workload(&random.to_string()).keys());
+        assert_ne!(random.keys(), workload("random 200 --seed 10").keys());
+        // a workload that has a seed keeps it
+        assert_eq!(random.clone().with_seed(), random);
+        let picked = workload("random 200").with_seed();
+        assert_eq!(picked.keys(), workload(&picked.to_string()).keys());
+    }
+
+    #[test]
+    fn random_keys_are_distinct_and_inside_the_range() {
+        for line in ["random 50 --seed 3 --range -20..40", "random 60 --seed 3 --range -20..40", "random 5 --seed 1 --range -9223372036854775808..9223372036854775807"] {
+            let random = workload(line);
+            let (low, high) = random.bounds();
+            let mut keys = random.keys();
+            assert!(keys.iter().all(|key| (low..=high).contains(key)), "{}: {:?}", line, keys);
+            let count = keys.len();
+            keys.sort();
+            keys.dedup();
+            assert_eq!(keys.len(), count, "{}", line);
+        }
+        // a range just big enough holds every key once
+        let mut keys = workload("random 60 --seed 3 --range -20..40").keys();
+        keys.sort();
+        assert_eq!(keys, (-20..40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sequences_come_in_their_order() {
+        assert_eq!(workload("sequence sorted 4").keys(), [0, 1, 2, 3]);
+        assert_eq!(workload("sequence reverse 4").keys(), [3, 2, 1, 0]);
+        assert_eq!(workload("sequence zigzag 1").keys(), [0]);
+        assert_eq!(workload("sequence zigzag 6").keys(), [0, 5, 1, 4, 2, 3]);
+        assert_eq!(workload("sequence zigzag 7").keys(), [0, 6, 1, 5, 2, 4, 3]);
+        assert_eq!(workload("sequence zigzag 7").bounds(), (0, 6));
+    }
+
+    #[test]
+    fn bad_workloads_are_refused() {
+        let error = |line: &str| parse_workload(&line.split_whitespace().map(str::to_string).collect::<Vec<_>>()).unwrap_err();
+        assert_eq!(error("random 11 --range 0..10"), "the range 0..10 holds fewer than 11 keys");
+        assert_eq!(error("random 5 --range 10..10"), "the range 10..10 is empty");
+        assert_eq!(error("random 5 --range 10"), "'10' is not a range like 0..100");
+        assert_eq!(error("random 5 --seed -1"), "'-1' is not a seed");
+        assert_eq!(error("random 1000001"), "count 1000001 is above the limit of 1000000");
+        assert_eq!(error("sequence spiral 5"), "unknown order 'spiral', expected sorted, reverse or zigzag");
+        assert_eq!(error("sequence sorted 5 6"), "usage: sequence sorted|reverse|zigzag COUNT");
+    }
+}